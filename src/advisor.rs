@@ -0,0 +1,187 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{luma_pixels, percentile, robust_sigma};
+use crate::{CameraUnit, DynamicSerialImage, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Advice on how to change the exposure settings.
+pub enum ExposureAdvice {
+    /// The exposure is within the target zones.
+    Good,
+    /// Increase the exposure by the given factor.
+    IncreaseExposure(f64),
+    /// Reduce the exposure by the given factor.
+    ReduceExposure(f64),
+    /// The background and highlight targets can not both be met by changing the
+    /// exposure. Reduce the gain to increase the dynamic range.
+    ReduceGain,
+}
+
+impl Display for ExposureAdvice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExposureAdvice::Good => write!(f, "Exposure is good"),
+            ExposureAdvice::IncreaseExposure(factor) => {
+                write!(f, "Increase exposure {:.1}x", factor)
+            }
+            ExposureAdvice::ReduceExposure(factor) => write!(f, "Reduce exposure {:.1}x", factor),
+            ExposureAdvice::ReduceGain => write!(f, "Reduce gain"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Result of a frame analysis by an [`ExposureAdvisor`].
+pub struct ExposureReport {
+    /// The background level (median, in ADU above bias).
+    pub background: f64,
+    /// The highlight level (in ADU above bias).
+    pub highlights: f64,
+    /// The advice.
+    pub advice: ExposureAdvice,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Analyzes frame histograms against target zones ("expose to the right"): the
+/// background should be well above the read noise, while the highlights stay
+/// below saturation.
+///
+/// All levels are in ADU of the 16-bit luminance of the frame.
+pub struct ExposureAdvisor {
+    /// The bias level of the detector.
+    pub bias: f64,
+    /// The read noise of the detector.
+    pub read_noise: f64,
+    /// The minimum background level above bias, as a multiple of the read noise.
+    pub background_factor: f64,
+    /// The saturation level. Defaults to `u16::MAX`; set this to the saturation level
+    /// of cameras that do not scale their data to 16 bits, e.g.
+    /// [`crate::PixelBpp::full_scale`] of the camera bit depth.
+    pub saturation: f64,
+    /// The percentile (in `[0, 1]`) of pixels considered the highlights.
+    pub highlight_percentile: f64,
+    /// The maximum highlight level, as a fraction of the saturation level above bias.
+    pub highlight_limit: f64,
+}
+
+impl Default for ExposureAdvisor {
+    fn default() -> Self {
+        Self {
+            bias: 0.0,
+            read_noise: 10.0,
+            background_factor: 10.0,
+            saturation: u16::MAX as f64,
+            highlight_percentile: 0.999,
+            highlight_limit: 0.9,
+        }
+    }
+}
+
+impl ExposureAdvisor {
+    /// Analyze a frame.
+    pub fn analyze(&self, img: &DynamicSerialImage) -> ExposureReport {
+        let pixels = luma_pixels(img);
+        let background = (percentile(&pixels, 0.5) - self.bias).max(0.0);
+        let highlights = (percentile(&pixels, self.highlight_percentile) - self.bias).max(0.0);
+        let min_background = self.background_factor * self.read_noise;
+        let max_highlights = self.highlight_limit * (self.saturation - self.bias);
+        // Largest factor that keeps highlights in range, and smallest factor that
+        // lifts the background out of the read noise.
+        let max_factor = max_highlights / highlights.max(1.0);
+        let min_factor = min_background / background.max(1.0);
+        let advice = if min_factor > max_factor {
+            ExposureAdvice::ReduceGain
+        } else if highlights > max_highlights {
+            ExposureAdvice::ReduceExposure(1.0 / max_factor)
+        } else if background < min_background {
+            ExposureAdvice::IncreaseExposure(min_factor)
+        } else {
+            ExposureAdvice::Good
+        };
+        ExposureReport {
+            background,
+            highlights,
+            advice,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Measurement of a single (gain, exposure) combination of a settings sweep.
+pub struct SweepResult {
+    /// The gain (in raw units).
+    pub gain: i64,
+    /// The exposure time.
+    pub exposure: Duration,
+    /// The signal-to-noise ratio of the highlights over the background.
+    pub snr: f64,
+    /// The fraction of saturated pixels.
+    pub saturated: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Result of a settings sweep.
+pub struct SweepReport {
+    /// All measurements of the sweep.
+    pub results: Vec<SweepResult>,
+    /// The measurement with the highest signal-to-noise ratio among those within
+    /// the saturation limit, if any.
+    pub best: Option<SweepResult>,
+}
+
+/// Capture a short test frame for every combination of gains and exposures on the
+/// current scene, and find the combination maximizing the signal-to-noise ratio
+/// of the highlights (e.g. stars) over the background, subject to a limit on the
+/// fraction of saturated pixels.
+///
+/// The gain and exposure of the camera are restored afterwards.
+///
+/// # Arguments
+/// - `cam` - The camera.
+/// - `gains` - The gains (in raw units) to test.
+/// - `exposures` - The exposures to test.
+/// - `advisor` - Provides the saturation level and highlight percentile.
+/// - `max_saturated` - The maximum allowed fraction of saturated pixels.
+pub fn sweep_settings(
+    cam: &mut dyn CameraUnit,
+    gains: &[i64],
+    exposures: &[Duration],
+    advisor: &ExposureAdvisor,
+    max_saturated: f64,
+) -> Result<SweepReport, Error> {
+    let (gain, exposure) = (cam.get_gain_raw(), cam.get_exposure());
+    let res = gains
+        .iter()
+        .flat_map(|&g| exposures.iter().map(move |&e| (g, e)))
+        .map(|(gain, exposure)| {
+            cam.set_gain_raw(gain)?;
+            cam.set_exposure(exposure)?;
+            let pixels = luma_pixels(&cam.capture_image()?);
+            let background = percentile(&pixels, 0.5);
+            let signal = percentile(&pixels, advisor.highlight_percentile) - background;
+            let saturated = pixels
+                .iter()
+                .filter(|&&p| p as f64 >= advisor.saturation)
+                .count() as f64
+                / pixels.len().max(1) as f64;
+            Ok(SweepResult {
+                gain,
+                exposure,
+                snr: signal / robust_sigma(&pixels).max(1.0),
+                saturated,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>();
+    cam.set_gain_raw(gain)?;
+    cam.set_exposure(exposure)?;
+    let results = res?;
+    let best = results
+        .iter()
+        .filter(|r| r.saturated <= max_saturated)
+        .max_by(|a, b| a.snr.total_cmp(&b.snr))
+        .copied();
+    Ok(SweepReport { results, best })
+}
@@ -0,0 +1,279 @@
+//! Asynchronous counterparts of [`CameraUnit`](crate::CameraUnit) and
+//! [`CameraInfo`](crate::CameraInfo), for GUIs and network services. Requires the
+//! `async` feature.
+//!
+//! Blocking cameras are adapted with [`AsyncCamera`] and [`AsyncInfo`], which
+//! run the blocking calls on the blocking thread pool of the `tokio` runtime.
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::task::spawn_blocking;
+
+use crate::{
+    AnyCameraInfo, AnyCameraUnit, CameraControlHandle, CaptureOptions, DynamicSerialImage, Error,
+    ROI,
+};
+
+async fn blocking<T, F>(f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+    T: Send + 'static,
+{
+    spawn_blocking(f)
+        .await
+        .map_err(|e| Error::Message(format!("Blocking task failed: {}", e)))?
+}
+
+#[async_trait]
+/// Asynchronous counterpart of [`CameraUnit`](crate::CameraUnit).
+pub trait AsyncCameraUnit: Send + Sync {
+    /// Get the camera name.
+    async fn camera_name(&self) -> Result<String, Error>;
+
+    /// Capture an image.
+    async fn capture_image(&self) -> Result<DynamicSerialImage, Error>;
+
+    /// Start an exposure and return immediately.
+    async fn start_exposure(&self) -> Result<(), Error>;
+
+    /// Download the image captured in [`AsyncCameraUnit::start_exposure`].
+    async fn download_image(&self) -> Result<DynamicSerialImage, Error>;
+
+    /// Get exposure status.
+    async fn image_ready(&self) -> Result<bool, Error>;
+
+    /// Cancel an ongoing exposure.
+    async fn cancel_capture(&self) -> Result<(), Error>;
+
+    /// Set the exposure time.
+    async fn set_exposure(&self, exposure: Duration) -> Result<Duration, Error>;
+
+    /// Get the exposure time.
+    async fn get_exposure(&self) -> Result<Duration, Error>;
+
+    /// Set the gain (in raw units).
+    async fn set_gain_raw(&self, gain: i64) -> Result<i64, Error>;
+
+    /// Get the gain (in raw units).
+    async fn get_gain_raw(&self) -> Result<i64, Error>;
+
+    /// Set the region of interest.
+    async fn set_roi(&self, roi: &ROI) -> Result<ROI, Error>;
+
+    /// Get the region of interest.
+    async fn get_roi(&self) -> Result<ROI, Error>;
+}
+
+#[async_trait]
+/// Asynchronous counterpart of [`CameraInfo`](crate::CameraInfo).
+pub trait AsyncCameraInfo: Send + Sync {
+    /// Check if camera is ready.
+    async fn camera_ready(&self) -> bool;
+
+    /// Get the camera name.
+    async fn camera_name(&self) -> String;
+
+    /// Cancel an ongoing exposure.
+    async fn cancel_capture(&self) -> Result<(), Error>;
+
+    /// Check if the camera is currently capturing an image.
+    async fn is_capturing(&self) -> bool;
+
+    /// Get the detector temperature.
+    async fn get_temperature(&self) -> Option<f32>;
+
+    /// Set the target detector temperature.
+    async fn set_temperature(&self, temperature: f32) -> Result<f32, Error>;
+
+    /// Get the cooler state.
+    async fn get_cooler(&self) -> Option<bool>;
+
+    /// Set the cooler state.
+    async fn set_cooler(&self, on: bool) -> Result<(), Error>;
+
+    /// Get the cooler power.
+    async fn get_cooler_power(&self) -> Option<f32>;
+}
+
+#[derive(Clone)]
+/// Adapter running a blocking camera unit behind the [`AsyncCameraUnit`] interface.
+///
+/// The camera is shared through a [`CameraControlHandle`], and every call runs on
+/// the blocking thread pool. The lock is released while an exposure is in progress,
+/// so settings can be changed from other tasks between frames.
+pub struct AsyncCamera {
+    handle: CameraControlHandle,
+    options: CaptureOptions,
+    poll_interval: Duration,
+}
+
+impl AsyncCamera {
+    /// Wrap a camera unit.
+    pub fn new(cam: AnyCameraUnit) -> Self {
+        Self::from_handle(CameraControlHandle::new(cam))
+    }
+
+    /// Wrap a camera unit shared through a control handle.
+    pub fn from_handle(handle: CameraControlHandle) -> Self {
+        Self {
+            handle,
+            options: CaptureOptions::default(),
+            poll_interval: Duration::from_millis(10),
+        }
+    }
+
+    /// Get the control handle of the camera.
+    pub fn handle(&self) -> &CameraControlHandle {
+        &self.handle
+    }
+
+    /// Set the options (pre-exposure delay, readout timeout and clock) applied to
+    /// captures (see [`CameraControlHandle::capture_image`]).
+    pub fn set_capture_options(&mut self, options: CaptureOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the interval at which the exposure status is polled during captures.
+    pub fn set_poll_interval(&mut self, poll_interval: Duration) -> &mut Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    async fn call<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&CameraControlHandle) -> Result<T, Error> + Send + 'static,
+        T: Send + 'static,
+    {
+        let handle = self.handle.clone();
+        blocking(move || f(&handle)).await
+    }
+}
+
+#[async_trait]
+impl AsyncCameraUnit for AsyncCamera {
+    async fn camera_name(&self) -> Result<String, Error> {
+        self.call(|h| Ok(h.lock()?.camera_name().to_string())).await
+    }
+
+    async fn capture_image(&self) -> Result<DynamicSerialImage, Error> {
+        let (options, poll_interval) = (self.options.clone(), self.poll_interval);
+        self.call(move |h| h.capture_image(&options, poll_interval))
+            .await
+    }
+
+    async fn start_exposure(&self) -> Result<(), Error> {
+        self.call(|h| h.lock()?.start_exposure()).await
+    }
+
+    async fn download_image(&self) -> Result<DynamicSerialImage, Error> {
+        self.call(|h| h.lock()?.download_image()).await
+    }
+
+    async fn image_ready(&self) -> Result<bool, Error> {
+        self.call(|h| h.lock()?.image_ready()).await
+    }
+
+    async fn cancel_capture(&self) -> Result<(), Error> {
+        self.call(|h| h.cancel_capture()).await
+    }
+
+    async fn set_exposure(&self, exposure: Duration) -> Result<Duration, Error> {
+        self.call(move |h| h.set_exposure(exposure)).await
+    }
+
+    async fn get_exposure(&self) -> Result<Duration, Error> {
+        self.call(|h| h.get_exposure()).await
+    }
+
+    async fn set_gain_raw(&self, gain: i64) -> Result<i64, Error> {
+        self.call(move |h| h.set_gain_raw(gain)).await
+    }
+
+    async fn get_gain_raw(&self) -> Result<i64, Error> {
+        self.call(|h| h.get_gain_raw()).await
+    }
+
+    async fn set_roi(&self, roi: &ROI) -> Result<ROI, Error> {
+        let roi = *roi;
+        self.call(move |h| h.set_roi(&roi)).await
+    }
+
+    async fn get_roi(&self) -> Result<ROI, Error> {
+        self.call(|h| h.get_roi()).await
+    }
+}
+
+#[derive(Clone)]
+/// Adapter running a blocking camera info object behind the [`AsyncCameraInfo`]
+/// interface. Every call that may block runs on the blocking thread pool.
+pub struct AsyncInfo(AnyCameraInfo);
+
+impl AsyncInfo {
+    /// Wrap a camera info object.
+    pub fn new(info: AnyCameraInfo) -> Self {
+        Self(info)
+    }
+
+    /// Get the wrapped camera info object.
+    pub fn inner(&self) -> &AnyCameraInfo {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl AsyncCameraInfo for AsyncInfo {
+    async fn camera_ready(&self) -> bool {
+        let cam = self.0.clone();
+        blocking(move || Ok(cam.camera_ready()))
+            .await
+            .unwrap_or(false)
+    }
+
+    async fn camera_name(&self) -> String {
+        self.0.camera_name().to_string()
+    }
+
+    async fn cancel_capture(&self) -> Result<(), Error> {
+        let cam = self.0.clone();
+        blocking(move || cam.cancel_capture()).await
+    }
+
+    async fn is_capturing(&self) -> bool {
+        let cam = self.0.clone();
+        blocking(move || Ok(cam.is_capturing()))
+            .await
+            .unwrap_or(false)
+    }
+
+    async fn get_temperature(&self) -> Option<f32> {
+        let cam = self.0.clone();
+        blocking(move || Ok(cam.get_temperature()))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    async fn set_temperature(&self, temperature: f32) -> Result<f32, Error> {
+        let cam = self.0.clone();
+        blocking(move || cam.set_temperature(temperature)).await
+    }
+
+    async fn get_cooler(&self) -> Option<bool> {
+        let cam = self.0.clone();
+        blocking(move || Ok(cam.get_cooler())).await.ok().flatten()
+    }
+
+    async fn set_cooler(&self, on: bool) -> Result<(), Error> {
+        let cam = self.0.clone();
+        blocking(move || cam.set_cooler(on)).await
+    }
+
+    async fn get_cooler_power(&self) -> Option<f32> {
+        let cam = self.0.clone();
+        blocking(move || Ok(cam.get_cooler_power()))
+            .await
+            .ok()
+            .flatten()
+    }
+}
@@ -0,0 +1,267 @@
+use std::fs::{remove_file, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{Error, PixelBpp, SharedClock, ROI};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Timing and size of a single frame in a stream.
+pub struct FrameRecord {
+    /// The exposure time of the frame.
+    pub exposure: Duration,
+    /// The time taken to acquire the frame, from the start of the exposure to the
+    /// completion of the download (and any processing that counts towards the cadence).
+    pub frame_time: Duration,
+    /// The size of the frame (in bytes) as written to the output.
+    pub bytes: usize,
+}
+
+/// A policy deciding the delay to insert between frames of a stream.
+pub trait CadencePolicy: Send {
+    /// Get the delay before the next frame, given the record of the last frame.
+    fn next_delay(&mut self, frame: &FrameRecord) -> Duration;
+}
+
+/// Get the time left of a frame period after the frame time, saturating at
+/// [`Duration::MAX`] for periods too long to represent.
+fn remaining(period: f64, frame_time: Duration) -> Duration {
+    Duration::try_from_secs_f64((period - frame_time.as_secs_f64()).max(0.0))
+        .unwrap_or(Duration::MAX)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Keep the fraction of time spent exposing at or below a target duty cycle.
+pub struct DutyCyclePolicy {
+    duty_cycle: f64,
+}
+
+impl DutyCyclePolicy {
+    /// Create a new duty cycle policy.
+    ///
+    /// # Arguments
+    /// - `duty_cycle` - The target fraction of time spent exposing, in `(0, 1]`.
+    ///   Values outside the range are clamped.
+    pub fn new(duty_cycle: f64) -> Self {
+        Self {
+            duty_cycle: duty_cycle.clamp(f64::EPSILON, 1.0),
+        }
+    }
+}
+
+impl CadencePolicy for DutyCyclePolicy {
+    fn next_delay(&mut self, frame: &FrameRecord) -> Duration {
+        let period = frame.exposure.as_secs_f64() / self.duty_cycle;
+        remaining(period, frame.frame_time)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Keep the average output data rate at or below a limit, e.g. the sustained
+/// write throughput of the disk.
+pub struct DataRatePolicy {
+    max_rate: f64,
+}
+
+impl DataRatePolicy {
+    /// Create a new data rate policy.
+    ///
+    /// # Arguments
+    /// - `max_rate` - The maximum data rate, in bytes per second.
+    pub fn new(max_rate: f64) -> Self {
+        Self {
+            max_rate: max_rate.max(f64::EPSILON),
+        }
+    }
+}
+
+impl CadencePolicy for DataRatePolicy {
+    fn next_delay(&mut self, frame: &FrameRecord) -> Duration {
+        let period = frame.bytes as f64 / self.max_rate;
+        remaining(period, frame.frame_time)
+    }
+}
+
+/// Adjusts the delay between frames of a stream according to a [`CadencePolicy`].
+pub struct CadenceController {
+    policy: Box<dyn CadencePolicy>,
+    min_delay: Duration,
+    max_delay: Duration,
+    clock: SharedClock,
+}
+
+impl CadenceController {
+    /// Create a new cadence controller.
+    ///
+    /// # Arguments
+    /// - `policy` - The cadence policy.
+    pub fn new(policy: Box<dyn CadencePolicy>) -> Self {
+        Self {
+            policy,
+            min_delay: Duration::ZERO,
+            max_delay: Duration::MAX,
+            clock: SharedClock::default(),
+        }
+    }
+
+    /// Set the clock used to wait between frames.
+    pub fn set_clock(&mut self, clock: SharedClock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Set the range of delays the controller may insert between frames.
+    pub fn set_delay_limits(&mut self, min: Duration, max: Duration) -> &mut Self {
+        self.min_delay = min.min(max);
+        self.max_delay = max.max(min);
+        self
+    }
+
+    /// Get the delay before the next frame, given the record of the last frame.
+    pub fn next_delay(&mut self, frame: &FrameRecord) -> Duration {
+        self.policy
+            .next_delay(frame)
+            .clamp(self.min_delay, self.max_delay)
+    }
+
+    /// Wait for the delay before the next frame, given the record of the last frame.
+    ///
+    /// # Returns
+    /// The delay that was inserted.
+    pub fn throttle(&mut self, frame: &FrameRecord) -> Duration {
+        let delay = self.next_delay(frame);
+        if !delay.is_zero() {
+            self.clock.sleep(delay);
+        }
+        delay
+    }
+}
+
+/// Estimate the data rate (in bytes per second) of a stream of raw frames.
+///
+/// Pixels with bit depths that are not a multiple of 8 are assumed to be stored
+/// in the next larger whole number of bytes.
+///
+/// # Arguments
+/// - `roi` - The region of interest of the frames.
+/// - `bpp` - The pixel format of the frames.
+/// - `fps` - The frame rate.
+pub fn estimate_data_rate(roi: &ROI, bpp: PixelBpp, fps: f64) -> f64 {
+    let bytes_per_pixel = match bpp {
+        PixelBpp::Bpp8 => 1,
+        PixelBpp::Bpp10 | PixelBpp::Bpp12 | PixelBpp::Bpp16 => 2,
+        PixelBpp::Bpp24 => 3,
+        PixelBpp::Bpp32 => 4,
+    };
+    roi.width as f64 * roi.height as f64 * bytes_per_pixel as f64 * fps
+}
+
+/// Measure the sustained write throughput (in bytes per second) of the disk
+/// holding a directory, by writing and removing a temporary file.
+///
+/// # Arguments
+/// - `dir` - The directory to measure.
+/// - `size` - The number of bytes to write. This should be at least a few frames.
+pub fn measure_write_throughput(dir: &Path, size: usize) -> Result<f64, Error> {
+    let path = dir.join(format!(".cameraunit-throughput-{}", std::process::id()));
+    let map_err = |e: std::io::Error| Error::InvalidPath(format!("{}: {}", path.display(), e));
+    let chunk = vec![0u8; size.clamp(1, 1 << 20)];
+    let start = Instant::now();
+    let mut file = File::create(&path).map_err(map_err)?;
+    let mut written = 0;
+    while written < size {
+        let len = chunk.len().min(size - written);
+        file.write_all(&chunk[..len]).map_err(map_err)?;
+        written += len;
+    }
+    file.sync_all().map_err(map_err)?;
+    let elapsed = start.elapsed().as_secs_f64();
+    drop(file);
+    remove_file(&path).map_err(map_err)?;
+    Ok(written as f64 / elapsed.max(f64::EPSILON))
+}
+
+/// Check that a planned acquisition does not exceed the write throughput of the
+/// disk holding the output directory, and warn if it does.
+///
+/// # Arguments
+/// - `dir` - The output directory.
+/// - `roi` - The region of interest of the frames.
+/// - `bpp` - The pixel format of the frames.
+/// - `fps` - The planned frame rate.
+///
+/// # Returns
+/// `true` if the disk can sustain the planned data rate.
+pub fn check_data_rate(dir: &Path, roi: &ROI, bpp: PixelBpp, fps: f64) -> Result<bool, Error> {
+    let rate = estimate_data_rate(roi, bpp, fps);
+    let throughput = measure_write_throughput(dir, (rate as usize).clamp(1 << 20, 1 << 28))?;
+    if rate > throughput {
+        log::warn!(
+            "Planned data rate {:.1} MB/s exceeds disk throughput {:.1} MB/s at {}",
+            rate / 1e6,
+            throughput / 1e6,
+            dir.display()
+        );
+    }
+    Ok(rate <= throughput)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::TestClock;
+
+    fn frame(exposure: Duration, frame_time: Duration, bytes: usize) -> FrameRecord {
+        FrameRecord {
+            exposure,
+            frame_time,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn duty_cycle_policy() {
+        let mut policy = DutyCyclePolicy::new(0.5);
+        let delay = policy.next_delay(&frame(
+            Duration::from_secs(1),
+            Duration::from_millis(1200),
+            0,
+        ));
+        assert_eq!(delay, Duration::from_millis(800));
+        let delay = policy.next_delay(&frame(Duration::from_secs(1), Duration::from_secs(3), 0));
+        assert_eq!(delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn duty_cycle_policy_saturates() {
+        let mut policy = DutyCyclePolicy::new(0.0);
+        let delay = policy.next_delay(&frame(Duration::MAX, Duration::ZERO, 0));
+        assert_eq!(delay, Duration::MAX);
+    }
+
+    #[test]
+    fn data_rate_policy() {
+        let mut policy = DataRatePolicy::new(1000.0);
+        let delay = policy.next_delay(&frame(Duration::ZERO, Duration::from_millis(500), 2000));
+        assert_eq!(delay, Duration::from_millis(1500));
+        let mut policy = DataRatePolicy::new(-1.0);
+        let delay = policy.next_delay(&frame(Duration::ZERO, Duration::ZERO, usize::MAX));
+        assert_eq!(delay, Duration::MAX);
+    }
+
+    #[test]
+    fn cadence_controller_throttles_on_clock() {
+        let clock = Arc::new(TestClock::default());
+        let mut controller = CadenceController::new(Box::new(DutyCyclePolicy::new(0.25)));
+        controller
+            .set_clock(SharedClock::new(clock.clone()))
+            .set_delay_limits(Duration::from_millis(100), Duration::from_secs(2));
+        let record = frame(Duration::from_secs(1), Duration::from_secs(1), 0);
+        assert_eq!(controller.throttle(&record), Duration::from_secs(2));
+        let record = frame(Duration::from_secs(1), Duration::from_secs(5), 0);
+        assert_eq!(controller.throttle(&record), Duration::from_millis(100));
+        assert_eq!(clock.elapsed(), Duration::from_millis(2100));
+    }
+}
@@ -0,0 +1,415 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::luma_pixels;
+use crate::{
+    CameraConfig, CameraUnit, Clock, DynamicSerialImage, Error, ImageMetaData, OptimumExposure,
+    SharedClock, ROI,
+};
+
+/// Poll until `ready` returns `true`.
+///
+/// # Errors
+/// [`Error::TimedOut`] if `ready` does not return `true` within the timeout, or any
+/// error returned by `ready`.
+pub(crate) fn wait_until_ready<F>(
+    clock: &dyn Clock,
+    poll_interval: Duration,
+    timeout: Duration,
+    mut ready: F,
+) -> Result<(), Error>
+where
+    F: FnMut() -> Result<bool, Error>,
+{
+    let start = clock.now();
+    while !ready()? {
+        if clock.now().duration_since(start) > timeout {
+            return Err(Error::TimedOut);
+        }
+        clock.sleep(poll_interval);
+    }
+    Ok(())
+}
+
+/// Meter the exposure on a (binned) subframe.
+///
+/// A single frame is captured with the region of interest set to `roi` at the
+/// current exposure, and the optimum exposure is calculated from it using `target`.
+/// Using a small, binned region of interest keeps metering fast, and does not
+/// require a full-resolution throwaway frame. The region of interest of the
+/// camera is restored afterwards.
+///
+/// # Arguments
+/// - `cam` - The camera to meter with.
+/// - `roi` - The region of interest (including binning) to meter on.
+/// - `target` - The optimum exposure configuration.
+///
+/// # Returns
+/// The optimum exposure and binning factor, or error.
+pub fn meter_exposure(
+    cam: &mut dyn CameraUnit,
+    roi: &ROI,
+    target: &OptimumExposure,
+) -> Result<(Duration, u16), Error> {
+    let orig = *cam.get_roi();
+    let exposure = cam.get_exposure();
+    let img = cam
+        .set_roi(roi)
+        .map(|_| ())
+        .and_then(|_| cam.capture_image());
+    cam.set_roi(&orig)?;
+    let img = img?;
+    let bin = img
+        .get_metadata()
+        .map_or(roi.bin_x, |meta| meta.bin_x)
+        .clamp(1, u8::MAX as u32) as u8;
+    target
+        .calculate(luma_pixels(&img), exposure, bin)
+        .map_err(|e| Error::Message(e.to_string()))
+}
+
+/// Record the exposure time of an image (in seconds) in the non-standard `EXPPREC`
+/// key of the image metadata.
+///
+/// The exposure time of [`ImageMetaData`] is written to FITS files in integer
+/// microseconds. The `EXPPREC` key keeps sub-microsecond exposure times, e.g.
+/// as reported by [`CameraUnit::get_exposure_precise`]. Extended attributes are
+/// written as string-valued keys, so the value is the decimal text of the exposure
+/// time; a numeric `EXPTIME` key needs numeric header support in serialimage.
+///
+/// # Arguments
+/// - `meta` - The image metadata.
+/// - `exposure_ps` - The exposure time in picoseconds.
+pub fn record_exposure(meta: &mut ImageMetaData, exposure_ps: u128) {
+    meta.add_extended_attrib("EXPPREC", &(exposure_ps as f64 * 1e-12).to_string());
+}
+
+/// Assigns monotonically increasing sequence numbers to captured frames.
+///
+/// Frames captured through [`FrameCounter::capture_image`] and
+/// [`FrameCounter::download_image`] (or passed to [`FrameCounter::stamp`]) have
+/// the frame number (`FRAMENO`) and session ID (`SESSION`) added to their metadata,
+/// so that log entries can be correlated with files. The counter can be shared
+/// between threads.
+#[derive(Debug)]
+pub struct FrameCounter {
+    session: String,
+    next: AtomicU64,
+}
+
+impl Default for FrameCounter {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::with_session(&format!("{:x}-{:x}", now.as_millis(), std::process::id()))
+    }
+}
+
+impl FrameCounter {
+    /// Create a new frame counter with a session ID derived from the current
+    /// time and process ID.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new frame counter with the given session ID.
+    pub fn with_session(session: &str) -> Self {
+        Self {
+            session: session.to_string(),
+            next: AtomicU64::new(1),
+        }
+    }
+
+    /// Get the session ID.
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// Get the number of frames counted so far.
+    pub fn count(&self) -> u64 {
+        self.next.load(Ordering::SeqCst) - 1
+    }
+
+    /// Assign the next frame number to an image.
+    ///
+    /// # Returns
+    /// The frame number assigned to the image.
+    pub fn stamp(&self, img: &mut DynamicSerialImage) -> u64 {
+        let frameno = self.next.fetch_add(1, Ordering::SeqCst);
+        if let Some(mut meta) = img.get_metadata() {
+            meta.add_extended_attrib("FRAMENO", &frameno.to_string());
+            meta.add_extended_attrib("SESSION", &self.session);
+            img.set_metadata(meta);
+        }
+        frameno
+    }
+
+    /// Capture an image (see [`CaptureOptions::capture_image`]) and assign it the next frame number.
+    pub fn capture_image(
+        &self,
+        cam: &dyn CameraUnit,
+        options: &CaptureOptions,
+    ) -> Result<DynamicSerialImage, Error> {
+        let mut img = options.capture_image(cam)?;
+        self.stamp(&mut img);
+        Ok(img)
+    }
+
+    /// Download an image (see [`CameraUnit::download_image`]) and assign it the next frame number.
+    pub fn download_image(&self, cam: &dyn CameraUnit) -> Result<DynamicSerialImage, Error> {
+        let mut img = cam.download_image()?;
+        self.stamp(&mut img);
+        Ok(img)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Options applied uniformly to captures made through the capture helpers.
+pub struct CaptureOptions {
+    pre_exposure_delay: Duration,
+    #[serde(default = "default_readout_timeout")]
+    readout_timeout: Duration,
+    #[serde(skip)]
+    clock: SharedClock,
+}
+
+fn default_readout_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            pre_exposure_delay: Duration::ZERO,
+            readout_timeout: default_readout_timeout(),
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+impl CaptureOptions {
+    /// Create a new set of capture options with no pre-exposure delay, and a
+    /// readout timeout of 30 seconds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delay inserted before every exposure, e.g. to let vibrations settle
+    /// after a mechanical shutter or mirror moves (mirror lockup).
+    pub fn set_pre_exposure_delay(&mut self, delay: Duration) -> &mut Self {
+        self.pre_exposure_delay = delay;
+        self
+    }
+
+    /// Get the delay inserted before every exposure.
+    pub fn get_pre_exposure_delay(&self) -> Duration {
+        self.pre_exposure_delay
+    }
+
+    /// Set the time allowed for the readout once the exposure time has elapsed,
+    /// before a capture that polls the exposure status times out.
+    pub fn set_readout_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.readout_timeout = timeout;
+        self
+    }
+
+    /// Get the time allowed for the readout once the exposure time has elapsed.
+    pub fn get_readout_timeout(&self) -> Duration {
+        self.readout_timeout
+    }
+
+    /// Set the clock used for waits and timing.
+    pub fn set_clock(&mut self, clock: SharedClock) -> &mut Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Get the clock used for waits and timing.
+    pub fn get_clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    pub(crate) fn settle(&self) {
+        if !self.pre_exposure_delay.is_zero() {
+            self.clock.sleep(self.pre_exposure_delay);
+        }
+    }
+
+    /// Capture an image (see [`CameraUnit::capture_image`]) after the pre-exposure delay.
+    pub fn capture_image(&self, cam: &dyn CameraUnit) -> Result<DynamicSerialImage, Error> {
+        self.settle();
+        cam.capture_image()
+    }
+
+    /// Start an exposure (see [`CameraUnit::start_exposure`]) after the pre-exposure delay.
+    pub fn start_exposure(&self, cam: &dyn CameraUnit) -> Result<(), Error> {
+        self.settle();
+        cam.start_exposure()
+    }
+}
+
+/// An exposure started with a snapshot of the camera settings, so that the image
+/// metadata reflects the settings at the start of the exposure even if they are
+/// changed before the image is downloaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExposureSnapshot {
+    config: CameraConfig,
+    exposure_ps: u128,
+}
+
+impl ExposureSnapshot {
+    /// Snapshot the camera settings and start an exposure (see [`CaptureOptions::start_exposure`]).
+    pub fn start(cam: &dyn CameraUnit, options: &CaptureOptions) -> Result<Self, Error> {
+        let config = CameraConfig::from_camera(cam);
+        let exposure_ps = cam.get_exposure_precise();
+        options.start_exposure(cam)?;
+        Ok(Self {
+            config,
+            exposure_ps,
+        })
+    }
+
+    /// Get the settings at the start of the exposure.
+    pub fn config(&self) -> &CameraConfig {
+        &self.config
+    }
+
+    /// Download the image (see [`CameraUnit::download_image`]), and set the exposure,
+    /// gain, binning and origin (in binned pixels, see [`ROI::check_image`]) in the
+    /// image metadata to the snapshot values. The precise exposure time is also
+    /// recorded (see [`record_exposure`]).
+    pub fn download_image(&self, cam: &dyn CameraUnit) -> Result<DynamicSerialImage, Error> {
+        let mut img = cam.download_image()?;
+        if let Some(mut meta) = img.get_metadata() {
+            let config = &self.config;
+            meta.exposure = config.exposure;
+            record_exposure(&mut meta, self.exposure_ps);
+            meta.bin_x = config.roi.bin_x;
+            meta.bin_y = config.roi.bin_y;
+            meta.img_left = config.roi.x_min;
+            meta.img_top = config.roi.y_min;
+            if let Some(gain) = config.gain {
+                meta.gain = gain;
+            }
+            img.set_metadata(meta);
+        }
+        Ok(img)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Timing breakdown of a single frame, for measuring end-to-end latency.
+///
+/// All intervals are measured on a monotonic clock, relative to the time the
+/// exposure was commanded.
+pub struct FrameTiming {
+    /// The time the exposure was commanded.
+    pub commanded: SystemTime,
+    /// The estimated opening of the shutter, taken as the return of [`CameraUnit::start_exposure`].
+    pub shutter_open: Duration,
+    /// The estimated start of the readout, taken as the first time [`CameraUnit::image_ready`]
+    /// reported the exposure as finished.
+    pub readout_start: Duration,
+    /// The completion of the download.
+    pub download_complete: Duration,
+}
+
+impl FrameTiming {
+    /// Capture an image through [`CameraUnit::start_exposure`] and
+    /// [`CameraUnit::download_image`], and record its timing.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `options` - The capture options. The pre-exposure delay is applied before
+    ///   the exposure is commanded, and timing is measured on the clock of the options.
+    /// - `poll_interval` - The interval at which [`CameraUnit::image_ready`] is polled.
+    ///
+    /// # Errors
+    /// [`Error::TimedOut`] if the image is not ready within the exposure time and the
+    /// readout timeout of the options. The capture is cancelled in that case.
+    pub fn capture(
+        cam: &dyn CameraUnit,
+        options: &CaptureOptions,
+        poll_interval: Duration,
+    ) -> Result<(DynamicSerialImage, Self), Error> {
+        options.settle();
+        let clock = options.get_clock();
+        let commanded = clock.wall();
+        let start = clock.now();
+        let elapsed = || clock.now().duration_since(start);
+        let timeout = cam
+            .get_exposure()
+            .saturating_add(options.get_readout_timeout());
+        cam.start_exposure()?;
+        let shutter_open = elapsed();
+        if let Err(e) = wait_until_ready(&**clock, poll_interval, timeout, || cam.image_ready()) {
+            if let Err(err) = cam.cancel_capture() {
+                log::warn!("{}: Failed to cancel capture: {}", cam.camera_name(), err);
+            }
+            return Err(e);
+        }
+        let readout_start = elapsed();
+        let mut img = cam.download_image()?;
+        let timing = Self {
+            commanded,
+            shutter_open,
+            readout_start,
+            download_complete: elapsed(),
+        };
+        if let Some(mut meta) = img.get_metadata() {
+            timing.update_metadata(&mut meta);
+            img.set_metadata(meta);
+        }
+        Ok((img, timing))
+    }
+
+    /// Get the time taken to read out and download the image.
+    pub fn download_time(&self) -> Duration {
+        self.download_complete.saturating_sub(self.readout_start)
+    }
+
+    /// Add the command latency (`CMDLAT`) and download time (`DLTIME`), in seconds,
+    /// to the image metadata.
+    pub fn update_metadata(&self, meta: &mut ImageMetaData) {
+        meta.add_extended_attrib("CMDLAT", &format!("{:.6}", self.shutter_open.as_secs_f64()));
+        meta.add_extended_attrib(
+            "DLTIME",
+            &format!("{:.6}", self.download_time().as_secs_f64()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestClock;
+
+    #[test]
+    fn wait_until_ready_polls_on_clock() {
+        let clock = TestClock::default();
+        let poll = Duration::from_millis(100);
+        let mut polls = 0;
+        let res = wait_until_ready(&clock, poll, Duration::from_secs(1), || {
+            polls += 1;
+            Ok(polls > 3)
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(clock.elapsed(), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn wait_until_ready_times_out() {
+        let clock = TestClock::default();
+        let poll = Duration::from_millis(100);
+        let res = wait_until_ready(&clock, poll, Duration::from_secs(1), || Ok(false));
+        assert_eq!(res, Err(Error::TimedOut));
+        assert_eq!(clock.elapsed(), Duration::from_millis(1100));
+        let res = wait_until_ready(&clock, poll, Duration::from_secs(1), || {
+            Err(Error::CameraRemoved)
+        });
+        assert_eq!(res, Err(Error::CameraRemoved));
+    }
+}
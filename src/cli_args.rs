@@ -0,0 +1,129 @@
+//! Standard command line options for tools built on this crate, using
+//! [`clap`](https://crates.io/crates/clap). Requires the `cli` feature.
+//!
+//! Flatten the structures into the argument parser of a tool:
+//! ```ignore
+//! #[derive(clap::Parser)]
+//! struct Cli {
+//!     #[command(flatten)]
+//!     camera: cameraunit::cli_args::CameraArgs,
+//!     #[command(flatten)]
+//!     output: cameraunit::cli_args::OutputArgs,
+//! }
+//! ```
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+
+use crate::{CameraUnit, Error, PixelBpp, ROI};
+
+fn parse_exposure(s: &str) -> Result<Duration, String> {
+    let secs: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(secs).map_err(|e| format!("{}", e))
+}
+
+fn parse_bpp(s: &str) -> Result<PixelBpp, String> {
+    match s.parse::<u32>() {
+        Ok(bpp @ (8 | 10 | 12 | 16 | 24 | 32)) => Ok(PixelBpp::from(bpp)),
+        _ => Err("expected one of 8, 10, 12, 16, 24 or 32".to_string()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Args)]
+/// Camera settings.
+pub struct CameraArgs {
+    /// Exposure time (in seconds).
+    #[arg(short, long, value_parser = parse_exposure)]
+    pub exposure: Option<Duration>,
+    /// Gain (in raw units).
+    #[arg(short, long)]
+    pub gain: Option<i64>,
+    /// Binning factor, applied along both axes.
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub bin: Option<u32>,
+    /// Region of interest as `x,y,width,height[,bin_x,bin_y[,skip_x,skip_y]]`, in binned pixels.
+    #[arg(long)]
+    pub roi: Option<ROI>,
+    /// Pixel bit depth.
+    #[arg(long, value_parser = parse_bpp)]
+    pub bpp: Option<PixelBpp>,
+}
+
+impl CameraArgs {
+    /// Apply the settings given on the command line to a camera.
+    ///
+    /// If only a binning factor is given, the region of interest is set to the
+    /// full detector at that binning. If both are given, the binning factor
+    /// overrides the binning of the region of interest.
+    pub fn apply_to(&self, cam: &mut dyn CameraUnit) -> Result<(), Error> {
+        if let Some(exposure) = self.exposure {
+            cam.set_exposure(exposure)?;
+        }
+        if let Some(gain) = self.gain {
+            cam.set_gain_raw(gain)?;
+        }
+        if let Some(bpp) = self.bpp {
+            cam.set_bpp(bpp)?;
+        }
+        let roi = match (self.roi, self.bin) {
+            (Some(roi), Some(bin)) => Some(ROI {
+                bin_x: bin,
+                bin_y: bin,
+                ..roi
+            }),
+            (Some(roi), None) => Some(roi),
+            (None, Some(bin)) => Some(ROI {
+                x_min: 0,
+                y_min: 0,
+                width: cam.get_ccd_width() / bin,
+                height: cam.get_ccd_height() / bin,
+                bin_x: bin,
+                bin_y: bin,
+                skip_x: 1,
+                skip_y: 1,
+            }),
+            (None, None) => None,
+        };
+        if let Some(roi) = roi {
+            cam.set_roi(&roi)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+/// Output image format.
+pub enum OutputFormat {
+    /// FITS, with metadata.
+    Fits,
+    /// PNG.
+    Png,
+    /// JPEG.
+    Jpeg,
+    /// BMP.
+    Bmp,
+}
+
+impl OutputFormat {
+    /// Get the file extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Fits => "fits",
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::Bmp => "bmp",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Args)]
+/// Output settings.
+pub struct OutputArgs {
+    /// Output directory.
+    #[arg(short, long, default_value = ".")]
+    pub output: PathBuf,
+    /// Output image format.
+    #[arg(short, long, value_enum, default_value = "fits")]
+    pub format: OutputFormat,
+}
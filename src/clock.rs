@@ -0,0 +1,243 @@
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// Source of time for timestamps, timeouts and waits.
+///
+/// All helpers in this crate that wait or measure time do so through a clock, so
+/// that sequence and timeout logic can be tested deterministically with a [`TestClock`].
+pub trait Clock: Send + Sync {
+    /// Get the current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// Get the current wall-clock time.
+    fn wall(&self) -> SystemTime;
+
+    /// Block for the given duration.
+    fn sleep(&self, duration: Duration);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// The system clock.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+#[derive(Debug)]
+/// A manually advanced clock for tests. Sleeping advances the clock immediately.
+pub struct TestClock {
+    instant: Instant,
+    wall: Mutex<SystemTime>,
+    elapsed: Mutex<Duration>,
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new(SystemTime::UNIX_EPOCH)
+    }
+}
+
+impl TestClock {
+    /// Create a new test clock starting at the given wall-clock time.
+    pub fn new(wall: SystemTime) -> Self {
+        Self {
+            instant: Instant::now(),
+            wall: Mutex::new(wall),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Advance the clock.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+        *self.wall.lock().unwrap() += duration;
+    }
+
+    /// Step the wall-clock time without advancing the monotonic time, e.g. to
+    /// simulate an NTP step.
+    pub fn set_wall(&self, wall: SystemTime) {
+        *self.wall.lock().unwrap() = wall;
+    }
+
+    /// Get the time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.instant + self.elapsed()
+    }
+
+    fn wall(&self) -> SystemTime {
+        *self.wall.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration)
+    }
+}
+
+#[derive(Clone)]
+/// A shared handle to a [`Clock`]. Defaults to the [`SystemClock`].
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    /// Create a new handle to a clock.
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self(clock)
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self(Arc::new(SystemClock))
+    }
+}
+
+impl Debug for SharedClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SharedClock")
+    }
+}
+
+impl PartialEq for SharedClock {
+    /// Two handles are equal if they refer to the same clock.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedClock {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// A step of the wall clock relative to the monotonic clock.
+pub struct ClockJump {
+    /// The wall-clock time after the step.
+    pub wall: SystemTime,
+    /// The size of the step (in seconds). Negative if the wall clock stepped back.
+    pub offset: f64,
+}
+
+/// Detects steps of the wall clock (e.g. NTP corrections) during a sequence.
+///
+/// Exposure and timeout logic should use monotonic time, converting to wall-clock
+/// time only for metadata through [`ClockJumpDetector::wall_time`]. The detector
+/// anchors the monotonic clock to the wall clock, and [`ClockJumpDetector::check`]
+/// reports when the two diverge.
+#[derive(Clone, Debug)]
+pub struct ClockJumpDetector {
+    clock: SharedClock,
+    threshold: Duration,
+    instant: Instant,
+    wall: SystemTime,
+}
+
+impl ClockJumpDetector {
+    /// Create a new detector anchored at the current time.
+    ///
+    /// # Arguments
+    /// - `clock` - The clock to monitor.
+    /// - `threshold` - The smallest divergence reported as a jump.
+    pub fn new(clock: SharedClock, threshold: Duration) -> Self {
+        let (instant, wall) = (clock.now(), clock.wall());
+        Self {
+            clock,
+            threshold,
+            instant,
+            wall,
+        }
+    }
+
+    /// Convert a monotonic time to wall-clock time, relative to the anchor.
+    pub fn wall_time(&self, instant: Instant) -> SystemTime {
+        match instant.checked_duration_since(self.instant) {
+            Some(after) => self.wall + after,
+            None => self.wall - self.instant.duration_since(instant),
+        }
+    }
+
+    /// Check for a wall-clock step since the anchor (or the last reported step).
+    ///
+    /// A warning is logged for every step, and the detector is re-anchored to the
+    /// new wall-clock time.
+    ///
+    /// # Returns
+    /// The step, or `None` if the divergence is below the threshold.
+    pub fn check(&mut self) -> Option<ClockJump> {
+        let (instant, wall) = (self.clock.now(), self.clock.wall());
+        let expected = self.wall_time(instant);
+        let offset = match wall.duration_since(expected) {
+            Ok(ahead) => ahead.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        };
+        if offset.abs() < self.threshold.as_secs_f64() {
+            return None;
+        }
+        log::warn!("Wall clock stepped by {:.3} s", offset);
+        self.instant = instant;
+        self.wall = wall;
+        Some(ClockJump { wall, offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_sleep_advances() {
+        let clock = TestClock::default();
+        let start = clock.now();
+        clock.sleep(Duration::from_secs(5));
+        assert_eq!(clock.now().duration_since(start), Duration::from_secs(5));
+        assert_eq!(
+            clock.wall(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn clock_jump_detector() {
+        let clock = Arc::new(TestClock::default());
+        let mut detector =
+            ClockJumpDetector::new(SharedClock::new(clock.clone()), Duration::from_secs(1));
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(detector.check(), None);
+
+        let wall = SystemTime::UNIX_EPOCH + Duration::from_secs(70);
+        clock.set_wall(wall);
+        assert_eq!(detector.check(), Some(ClockJump { wall, offset: 10.0 }));
+        assert_eq!(detector.wall_time(clock.now()), wall);
+        assert_eq!(detector.check(), None);
+
+        let wall = SystemTime::UNIX_EPOCH + Duration::from_secs(65);
+        clock.set_wall(wall);
+        assert_eq!(detector.check(), Some(ClockJump { wall, offset: -5.0 }));
+
+        clock.set_wall(wall + Duration::from_millis(500));
+        assert_eq!(detector.check(), None);
+    }
+}
@@ -0,0 +1,321 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CameraUnit, Clock, Error, PixelBpp, Quirks, ROI};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A snapshot of the configurable settings of a camera.
+pub struct CameraConfig {
+    /// The exposure time.
+    pub exposure: Duration,
+    /// The gain (in raw units).
+    pub gain: Option<i64>,
+    /// The pixel offset.
+    pub offset: Option<i32>,
+    /// The region of interest.
+    pub roi: ROI,
+    /// The pixel format.
+    pub bpp: PixelBpp,
+    /// Flip along the X and Y axes.
+    pub flip: Option<(bool, bool)>,
+    /// The commanded detector temperature set point (not the measured temperature).
+    pub temperature: Option<f32>,
+    /// The cooler state.
+    pub cooler: Option<bool>,
+}
+
+fn not_implemented(err: &Error) -> bool {
+    matches!(err.root(), Error::Message(msg) if msg == "Not implemented")
+}
+
+impl CameraConfig {
+    /// Get the current settings of a camera.
+    ///
+    /// The temperature set point is recorded if the camera reports it (see
+    /// [`CameraUnit::get_temperature_setpoint`]), and left unset otherwise.
+    pub fn from_camera(cam: &dyn CameraUnit) -> Self {
+        Self {
+            exposure: cam.get_exposure(),
+            gain: Some(cam.get_gain_raw()),
+            offset: Some(cam.get_offset()),
+            roi: *cam.get_roi(),
+            bpp: cam.get_bpp(),
+            flip: Some(cam.get_flip()),
+            temperature: cam.get_temperature_setpoint(),
+            cooler: cam.get_cooler(),
+        }
+    }
+
+    /// Parse settings from TOML (requires the `toml` feature).
+    ///
+    /// # Errors
+    /// [`Error::InvalidFormat`] if the input is not valid TOML, or does not describe
+    /// a camera configuration.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(data: &str) -> Result<Self, Error> {
+        toml::from_str(data).map_err(|e| Error::InvalidFormat(e.to_string()))
+    }
+
+    /// Apply the settings to a camera, and wait for the settle time declared in the
+    /// quirks of the camera (see [`CameraConfig::apply_with`]).
+    ///
+    /// Optional settings that the camera does not implement are skipped.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `clock` - The clock to wait on, e.g. [`crate::SystemClock`].
+    ///
+    /// # Errors
+    /// [`Error::InvalidValue`] if the region of interest does not satisfy the
+    /// alignment constraints of the camera (see [`Quirks`]).
+    pub fn apply(&self, cam: &mut dyn CameraUnit, clock: &dyn Clock) -> Result<(), Error> {
+        let quirks = cam.get_quirks();
+        self.apply_with(cam, &quirks, clock).map(|_| ())
+    }
+
+    /// Apply the settings to a camera, and wait for the settle time of the changed
+    /// settings before returning, so that the next exposure is free of artifacts.
+    ///
+    /// Optional settings that the camera does not implement are skipped.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `quirks` - The region of interest alignment and settle times to use.
+    /// - `clock` - The clock to wait on.
+    ///
+    /// # Returns
+    /// The settle time that was waited for, or error.
+    ///
+    /// # Errors
+    /// [`Error::InvalidValue`] if the region of interest does not satisfy the
+    /// alignment constraints in `quirks`.
+    pub fn apply_with(
+        &self,
+        cam: &mut dyn CameraUnit,
+        quirks: &Quirks,
+        clock: &dyn Clock,
+    ) -> Result<Duration, Error> {
+        quirks.check_roi(&self.roi)?;
+        let setpoint = cam.get_temperature_setpoint();
+        let settle = quirks.settle_time(
+            self.exposure != cam.get_exposure(),
+            matches!(self.gain, Some(gain) if gain != cam.get_gain_raw()),
+            self.temperature.is_some() && self.temperature != setpoint,
+        );
+        cam.set_exposure(self.exposure)?;
+        cam.set_bpp(self.bpp)?;
+        cam.set_roi(&self.roi)?;
+        let optional = [
+            self.gain.map(|gain| cam.set_gain_raw(gain).map(|_| ())),
+            self.offset.map(|offset| cam.set_offset(offset).map(|_| ())),
+            self.flip.map(|(x, y)| cam.set_flip(x, y)),
+            self.temperature
+                .map(|temperature| cam.set_temperature(temperature).map(|_| ())),
+            self.cooler.map(|on| cam.set_cooler(on)),
+        ];
+        for res in optional.into_iter().flatten() {
+            match res {
+                Err(err) if !not_implemented(&err) => return Err(err),
+                _ => {}
+            }
+        }
+        if !settle.is_zero() {
+            clock.sleep(settle);
+        }
+        Ok(settle)
+    }
+}
+
+/// Persistent store of camera settings, keyed by camera UUID.
+///
+/// Settings are stored as JSON files in a `cameraunit` directory under the
+/// platform configuration directory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsStore {
+    dir: PathBuf,
+}
+
+impl SettingsStore {
+    /// Create a settings store in the platform configuration directory
+    /// (`$XDG_CONFIG_HOME`, `~/.config`, `~/Library/Application Support` or `%APPDATA%`).
+    ///
+    /// # Errors
+    /// [`Error::InvalidPath`] if the configuration directory can not be determined.
+    pub fn new() -> Result<Self, Error> {
+        let env = |name| std::env::var_os(name).map(PathBuf::from);
+        let base = if cfg!(target_os = "windows") {
+            env("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            env("HOME").map(|home| home.join("Library").join("Application Support"))
+        } else {
+            env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+        }
+        .ok_or(Error::InvalidPath(
+            "Configuration directory not found".to_string(),
+        ))?;
+        Ok(Self::with_dir(&base.join("cameraunit")))
+    }
+
+    /// Create a settings store in the given directory.
+    pub fn with_dir(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Get the directory of the settings store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The file name is the UUID with every byte outside `[A-Za-z0-9-]` escaped as
+    /// `_XX` (hexadecimal), so that distinct UUIDs map to distinct files.
+    fn path(&self, uuid: &str) -> PathBuf {
+        let mut name = String::with_capacity(uuid.len());
+        for b in uuid.bytes() {
+            if b.is_ascii_alphanumeric() || b == b'-' {
+                name.push(b as char);
+            } else {
+                name.push_str(&format!("_{:02X}", b));
+            }
+        }
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// Save the settings of a camera.
+    pub fn save(&self, uuid: &str, config: &CameraConfig) -> Result<(), Error> {
+        let path = self.path(uuid);
+        let data = serde_json::to_string_pretty(config)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))?;
+        fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&path, data))
+            .map_err(|e| Error::InvalidPath(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load the settings of a camera.
+    ///
+    /// # Returns
+    /// The stored settings, or `None` if no settings are stored for the camera.
+    pub fn load(&self, uuid: &str) -> Result<Option<CameraConfig>, Error> {
+        let path = self.path(uuid);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| Error::InvalidPath(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| Error::InvalidFormat(e.to_string()))
+    }
+
+    /// Save the current settings of a camera.
+    pub fn save_current(&self, cam: &dyn CameraUnit) -> Result<(), Error> {
+        self.save(uuid_of(cam)?, &CameraConfig::from_camera(cam))
+    }
+
+    /// Apply the last saved settings to a camera (see [`CameraConfig::apply`]).
+    ///
+    /// # Returns
+    /// `true` if settings were found and applied, `false` if no settings are stored
+    /// for the camera.
+    pub fn apply_last_known(
+        &self,
+        cam: &mut dyn CameraUnit,
+        clock: &dyn Clock,
+    ) -> Result<bool, Error> {
+        match self.load(uuid_of(cam)?)? {
+            Some(config) => config.apply(cam, clock).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+fn uuid_of(cam: &dyn CameraUnit) -> Result<&str, Error> {
+    cam.get_uuid()
+        .ok_or(Error::Message("Camera has no UUID".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CameraConfig {
+        CameraConfig {
+            exposure: Duration::from_millis(1500),
+            gain: Some(100),
+            offset: None,
+            roi: ROI {
+                x_min: 8,
+                y_min: 16,
+                width: 640,
+                height: 480,
+                bin_x: 2,
+                bin_y: 2,
+                skip_x: 1,
+                skip_y: 1,
+            },
+            bpp: PixelBpp::Bpp16,
+            flip: Some((true, false)),
+            temperature: Some(-10.0),
+            cooler: Some(true),
+        }
+    }
+
+    #[test]
+    fn settings_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SettingsStore::with_dir(&dir.path().join("cameraunit"));
+        assert_eq!(store.load("a/b"), Ok(None));
+        let uuids = ["a/b", "a:b", "a_b", "a_2Fb", "\u{e9}/..", ""];
+        for (gain, uuid) in uuids.iter().enumerate() {
+            let config = CameraConfig {
+                gain: Some(gain as i64),
+                ..config()
+            };
+            store.save(uuid, &config).unwrap();
+        }
+        for (gain, uuid) in uuids.iter().enumerate() {
+            let config = store.load(uuid).unwrap().unwrap();
+            assert_eq!(config.gain, Some(gain as i64), "{:?}", uuid);
+        }
+        assert_eq!(fs::read_dir(store.dir()).unwrap().count(), uuids.len());
+        assert_eq!(store.path("a/b"), store.dir().join("a_2Fb.json"));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml_round_trip() {
+        let config = config();
+        let data = toml::to_string(&config).unwrap();
+        assert_eq!(CameraConfig::from_toml(&data), Ok(config));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn from_toml_rejects_invalid_input() {
+        let data = toml::to_string(&config()).unwrap();
+        let truncated = &data[..data.len() / 2];
+        let inputs = [
+            "",
+            "\0\u{feff}[[[",
+            "exposure = \"1s\"",
+            truncated,
+            &data.replace("secs = 1", "secs = -1"),
+            &data.replace("secs = 1", "secs = 18446744073709551616"),
+            &data.replace("nanos = 500000000", "nanos = 1e400"),
+            &data.replace("bpp = \"Bpp16\"", "bpp = \"Bpp7\""),
+            &data.replace("width = 640", "width = \"640\""),
+            &data.replace("gain = 100", "gain = 9223372036854775808"),
+        ];
+        for input in inputs {
+            assert!(
+                matches!(CameraConfig::from_toml(input), Err(Error::InvalidFormat(_))),
+                "{:?}",
+                input
+            );
+        }
+    }
+}
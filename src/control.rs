@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::capture::wait_until_ready;
+use crate::{AnyCameraUnit, CaptureOptions, DynamicSerialImage, Error, ROI};
+
+#[derive(Clone)]
+/// A shared handle to a camera unit, with explicit locking.
+///
+/// The setters of [`crate::CameraUnit`] take `&mut self`, so changing a setting
+/// normally has to go through the thread that owns the camera. A control handle
+/// can be cloned and handed to other threads (e.g. a GUI), and every call only
+/// holds the lock for the duration of the underlying call. Captures through
+/// [`CameraControlHandle::capture_image`] release the lock while exposing, so
+/// settings can be changed between frames without stopping the capture loop.
+pub struct CameraControlHandle {
+    cam: Arc<Mutex<AnyCameraUnit>>,
+}
+
+impl CameraControlHandle {
+    /// Create a control handle that takes ownership of a camera unit.
+    pub fn new(cam: AnyCameraUnit) -> Self {
+        Self {
+            cam: Arc::new(Mutex::new(cam)),
+        }
+    }
+
+    /// Lock the camera unit for exclusive access.
+    ///
+    /// # Errors
+    /// [`Error::Message`] if a thread panicked while holding the lock.
+    pub fn lock(&self) -> Result<MutexGuard<'_, AnyCameraUnit>, Error> {
+        self.cam
+            .lock()
+            .map_err(|_| Error::Message("Camera lock poisoned".to_string()))
+    }
+
+    /// Set the exposure time (see [`crate::CameraUnit::set_exposure`]).
+    pub fn set_exposure(&self, exposure: Duration) -> Result<Duration, Error> {
+        self.lock()?.set_exposure(exposure)
+    }
+
+    /// Get the exposure time.
+    pub fn get_exposure(&self) -> Result<Duration, Error> {
+        Ok(self.lock()?.get_exposure())
+    }
+
+    /// Set the gain in percent (see [`crate::CameraUnit::set_gain`]).
+    pub fn set_gain(&self, gain: f32) -> Result<f32, Error> {
+        self.lock()?.set_gain(gain)
+    }
+
+    /// Set the gain in raw units (see [`crate::CameraUnit::set_gain_raw`]).
+    pub fn set_gain_raw(&self, gain: i64) -> Result<i64, Error> {
+        self.lock()?.set_gain_raw(gain)
+    }
+
+    /// Get the gain in raw units.
+    pub fn get_gain_raw(&self) -> Result<i64, Error> {
+        Ok(self.lock()?.get_gain_raw())
+    }
+
+    /// Set the region of interest (see [`crate::CameraUnit::set_roi`]).
+    ///
+    /// # Returns
+    /// The region of interest that was set, or error.
+    pub fn set_roi(&self, roi: &ROI) -> Result<ROI, Error> {
+        self.lock()?.set_roi(roi).copied()
+    }
+
+    /// Get the region of interest.
+    pub fn get_roi(&self) -> Result<ROI, Error> {
+        Ok(*self.lock()?.get_roi())
+    }
+
+    /// Capture an image through [`crate::CameraUnit::start_exposure`] and
+    /// [`crate::CameraUnit::download_image`], releasing the lock while the
+    /// exposure is in progress.
+    ///
+    /// # Arguments
+    /// - `options` - The capture options. The pre-exposure delay is applied before
+    ///   the exposure is started, and the exposure status is polled on the clock of
+    ///   the options.
+    /// - `poll_interval` - The interval at which [`crate::CameraUnit::image_ready`] is polled.
+    ///
+    /// # Errors
+    /// [`Error::TimedOut`] if the image is not ready within the exposure time and the
+    /// readout timeout of the options. The capture is cancelled in that case.
+    pub fn capture_image(
+        &self,
+        options: &CaptureOptions,
+        poll_interval: Duration,
+    ) -> Result<DynamicSerialImage, Error> {
+        options.settle();
+        let (name, exposure) = {
+            let cam = self.lock()?;
+            let exposure = cam.get_exposure();
+            cam.start_exposure()?;
+            (cam.camera_name().to_string(), exposure)
+        };
+        let timeout = exposure.saturating_add(options.get_readout_timeout());
+        let ready = || self.lock()?.image_ready();
+        if let Err(e) = wait_until_ready(&**options.get_clock(), poll_interval, timeout, ready) {
+            if let Err(err) = self.cancel_capture() {
+                log::warn!("{}: Failed to cancel capture: {}", name, err);
+            }
+            return Err(e);
+        }
+        self.lock()?.download_image()
+    }
+
+    /// Cancel an ongoing capture (see [`crate::CameraUnit::cancel_capture`]).
+    pub fn cancel_capture(&self) -> Result<(), Error> {
+        self.lock()?.cancel_capture()
+    }
+}
@@ -0,0 +1,366 @@
+use std::ops::Deref;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AnyCameraInfo, CameraInfo, Error, SharedClock};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Controlled change of the detector temperature set point, to avoid thermal
+/// shock and condensation on the sensor.
+pub struct CoolingRamp {
+    /// The maximum rate of change of the set point (in degrees C per minute).
+    pub rate: f32,
+    /// The interval between set point updates.
+    pub step_interval: Duration,
+    /// Maximum allowed deviation (in degrees C) of the detector temperature from the target.
+    pub tolerance: f32,
+    /// Maximum time to wait for the detector to reach the target after the ramp.
+    pub timeout: Duration,
+    /// The ambient temperature (in degrees C) to warm up to.
+    pub ambient: f32,
+    #[serde(skip)]
+    /// The clock used for waits and timeouts.
+    pub clock: SharedClock,
+}
+
+impl Default for CoolingRamp {
+    fn default() -> Self {
+        Self {
+            rate: 5.0,
+            step_interval: Duration::from_secs(10),
+            tolerance: 1.0,
+            timeout: Duration::from_secs(600),
+            ambient: 20.0,
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+impl CoolingRamp {
+    /// Ramp the set point from the current detector temperature to the target,
+    /// and wait for the detector to reach the target.
+    ///
+    /// # Returns
+    /// The detector temperature at the end of the ramp, or error.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidValue`] if the target or the ramp rate is not finite, or the
+    ///   rate is zero.
+    /// - [`Error::TimedOut`] if the detector does not reach the target in time.
+    pub fn ramp_to(&self, cam: &dyn CameraInfo, target: f32) -> Result<f32, Error> {
+        self.ramp(cam, target, |temperature| {
+            (temperature - target).abs() <= self.tolerance
+        })
+    }
+
+    fn ramp(
+        &self,
+        cam: &dyn CameraInfo,
+        target: f32,
+        reached: impl Fn(f32) -> bool,
+    ) -> Result<f32, Error> {
+        if !target.is_finite() {
+            return Err(Error::InvalidValue(format!(
+                "Target temperature: {}",
+                target
+            )));
+        }
+        let step = self.rate.abs() * self.step_interval.as_secs_f32() / 60.0;
+        if !step.is_finite() || step <= 0.0 {
+            return Err(Error::InvalidValue(format!("Ramp rate: {}", self.rate)));
+        }
+        let mut setpoint = read_temperature(cam)?;
+        while setpoint != target {
+            setpoint = if setpoint < target {
+                (setpoint + step).min(target)
+            } else {
+                (setpoint - step).max(target)
+            };
+            cam.set_temperature(setpoint)?;
+            self.clock.sleep(self.step_interval);
+        }
+        let start = self.clock.now();
+        loop {
+            let temperature = read_temperature(cam)?;
+            if reached(temperature) {
+                return Ok(temperature);
+            }
+            if self.clock.now().duration_since(start) > self.timeout {
+                return Err(Error::TimedOut);
+            }
+            self.clock.sleep(self.step_interval);
+        }
+    }
+
+    /// Turn the cooler on, and ramp the detector down to the target temperature.
+    ///
+    /// # Returns
+    /// The detector temperature at the end of the ramp, or error.
+    pub fn cool_down(&self, cam: &dyn CameraInfo, target: f32) -> Result<f32, Error> {
+        cam.set_temperature(read_temperature(cam)?)?;
+        cam.set_cooler(true)?;
+        self.ramp_to(cam, target)
+    }
+
+    /// Ramp the detector up to the ambient temperature. The ramp completes when the
+    /// detector is within tolerance of the ambient temperature, or the cooler power
+    /// drops to zero. The cooler is left on.
+    ///
+    /// # Returns
+    /// The detector temperature at the end of the ramp, or error.
+    pub fn warm_up(&self, cam: &dyn CameraInfo) -> Result<f32, Error> {
+        let temperature = read_temperature(cam)?;
+        if temperature >= self.ambient - self.tolerance {
+            return Ok(temperature);
+        }
+        self.ramp(cam, self.ambient, |temperature| {
+            (temperature - self.ambient).abs() <= self.tolerance
+                || matches!(cam.get_cooler_power(), Some(power) if power <= 0.0)
+        })
+    }
+}
+
+fn read_temperature(cam: &dyn CameraInfo) -> Result<f32, Error> {
+    cam.get_temperature().ok_or(Error::Message(
+        "Temperature readout not available".to_string(),
+    ))
+}
+
+/// Shut down the cooler gracefully.
+///
+/// If the cooler is on, the detector is ramped back to ambient temperature with
+/// the default [`CoolingRamp`] before the cooler is turned off, preventing
+/// condensation on the sensor. The cooler is turned off even if the warm-up fails,
+/// and the cooler is turned off immediately if the camera has no temperature readout.
+///
+/// # Errors
+/// The warm-up error (e.g. [`Error::TimedOut`]) if the detector did not reach
+/// ambient temperature before the cooler was turned off, or the error turning
+/// the cooler off.
+pub fn graceful_shutdown(cam: &dyn CameraInfo) -> Result<(), Error> {
+    if cam.get_cooler() == Some(false) {
+        return Ok(());
+    }
+    if cam.get_temperature().is_none() {
+        log::warn!(
+            "{}: Temperature readout not available, turning cooler off without warm-up",
+            cam.camera_name()
+        );
+        return cam.set_cooler(false);
+    }
+    let res = CoolingRamp::default().warm_up(cam);
+    cam.set_cooler(false)?;
+    res.map(|_| ())
+}
+
+/// Wrapper around a camera info object that warns if the camera is dropped
+/// without a [`graceful_shutdown`] while the cooler is on.
+pub struct CoolerGuard {
+    cam: AnyCameraInfo,
+    done: bool,
+}
+
+impl CoolerGuard {
+    /// Wrap a camera info object.
+    pub fn new(cam: AnyCameraInfo) -> Self {
+        Self { cam, done: false }
+    }
+
+    /// Shut down the cooler gracefully (see [`graceful_shutdown`]).
+    pub fn shutdown(mut self) -> Result<(), Error> {
+        graceful_shutdown(self.cam.as_ref().as_ref())?;
+        self.done = true;
+        Ok(())
+    }
+}
+
+impl Deref for CoolerGuard {
+    type Target = dyn CameraInfo;
+
+    fn deref(&self) -> &Self::Target {
+        self.cam.as_ref().as_ref()
+    }
+}
+
+impl Drop for CoolerGuard {
+    fn drop(&mut self) {
+        if !self.done && self.cam.get_cooler().unwrap_or(false) {
+            log::warn!(
+                "{}: Cooler still on at {:?} C without graceful shutdown",
+                self.cam.camera_name(),
+                self.cam.get_temperature()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::TestClock;
+
+    #[derive(Default)]
+    struct State {
+        temperature: Option<f32>,
+        follow: bool,
+        cooler: bool,
+        power: Option<f32>,
+        setpoints: Vec<f32>,
+        cooler_calls: usize,
+    }
+
+    struct MockCamera(Mutex<State>);
+
+    impl MockCamera {
+        fn new(temperature: Option<f32>, follow: bool) -> Self {
+            Self(Mutex::new(State {
+                temperature,
+                follow,
+                cooler: true,
+                ..Default::default()
+            }))
+        }
+
+        fn state(&self) -> std::sync::MutexGuard<'_, State> {
+            self.0.lock().unwrap()
+        }
+    }
+
+    impl CameraInfo for MockCamera {
+        fn camera_ready(&self) -> bool {
+            true
+        }
+
+        fn camera_name(&self) -> &str {
+            "mock"
+        }
+
+        fn cancel_capture(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn is_capturing(&self) -> bool {
+            false
+        }
+
+        fn set_temperature(&self, temperature: f32) -> Result<f32, Error> {
+            let mut state = self.state();
+            state.setpoints.push(temperature);
+            if state.follow {
+                state.temperature = Some(temperature);
+            }
+            Ok(temperature)
+        }
+
+        fn get_temperature(&self) -> Option<f32> {
+            self.state().temperature
+        }
+
+        fn set_cooler(&self, on: bool) -> Result<(), Error> {
+            let mut state = self.state();
+            state.cooler = on;
+            state.cooler_calls += 1;
+            Ok(())
+        }
+
+        fn get_cooler(&self) -> Option<bool> {
+            Some(self.state().cooler)
+        }
+
+        fn get_cooler_power(&self) -> Option<f32> {
+            self.state().power
+        }
+
+        fn get_ccd_width(&self) -> u32 {
+            0
+        }
+
+        fn get_ccd_height(&self) -> u32 {
+            0
+        }
+    }
+
+    fn ramp(clock: &Arc<TestClock>) -> CoolingRamp {
+        CoolingRamp {
+            rate: 6.0,
+            step_interval: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            clock: SharedClock::new(clock.clone()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ramp_steps_set_point() {
+        let clock = Arc::new(TestClock::default());
+        let cam = MockCamera::new(Some(0.0), true);
+        assert_eq!(ramp(&clock).ramp_to(&cam, -2.5).unwrap(), -2.5);
+        assert_eq!(cam.state().setpoints, vec![-1.0, -2.0, -2.5]);
+        assert_eq!(clock.elapsed(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn ramp_rejects_invalid_input() {
+        let clock = Arc::new(TestClock::default());
+        let cam = MockCamera::new(Some(0.0), true);
+        for target in [f32::NAN, f32::NEG_INFINITY] {
+            assert!(matches!(
+                ramp(&clock).ramp_to(&cam, target),
+                Err(Error::InvalidValue(_))
+            ));
+        }
+        for rate in [0.0, f32::NAN, f32::INFINITY] {
+            let ramp = CoolingRamp {
+                rate,
+                ..ramp(&clock)
+            };
+            assert!(matches!(
+                ramp.ramp_to(&cam, -10.0),
+                Err(Error::InvalidValue(_))
+            ));
+        }
+        assert!(cam.state().setpoints.is_empty());
+    }
+
+    #[test]
+    fn ramp_times_out() {
+        let clock = Arc::new(TestClock::default());
+        let cam = MockCamera::new(Some(0.0), false);
+        assert!(matches!(
+            ramp(&clock).ramp_to(&cam, -2.0),
+            Err(Error::TimedOut)
+        ));
+        assert_eq!(cam.state().setpoints, vec![-1.0, -2.0]);
+        assert!(clock.elapsed() > Duration::from_secs(80));
+    }
+
+    #[test]
+    fn warm_up_completes_on_zero_cooler_power() {
+        let clock = Arc::new(TestClock::default());
+        let cam = MockCamera::new(Some(-10.0), false);
+        cam.state().power = Some(0.0);
+        assert_eq!(ramp(&clock).warm_up(&cam).unwrap(), -10.0);
+        assert_eq!(cam.state().setpoints.last(), Some(&20.0));
+        assert!(cam.state().cooler);
+    }
+
+    #[test]
+    fn shutdown_with_cooler_off() {
+        let cam = MockCamera::new(Some(-10.0), true);
+        cam.state().cooler = false;
+        graceful_shutdown(&cam).unwrap();
+        assert!(cam.state().setpoints.is_empty());
+        assert_eq!(cam.state().cooler_calls, 0);
+    }
+
+    #[test]
+    fn shutdown_without_temperature_readout() {
+        let cam = MockCamera::new(None, true);
+        graceful_shutdown(&cam).unwrap();
+        assert!(!cam.state().cooler);
+        assert!(cam.state().setpoints.is_empty());
+    }
+}
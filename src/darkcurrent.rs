@@ -0,0 +1,224 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::mean_level;
+use crate::{CameraUnit, Error, SharedClock};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Configuration for a dark current profiling run.
+pub struct DarkCurrentConfig {
+    /// Exposure time of the dark frames.
+    pub exposure: Duration,
+    /// Exposure time of the bias frames, subtracted from the dark frames.
+    pub bias_exposure: Duration,
+    /// Maximum allowed deviation (in degrees C) of the detector temperature from the set point.
+    pub tolerance: f32,
+    /// Time the detector temperature has to remain within tolerance before capturing.
+    pub settle_time: Duration,
+    /// Maximum time to wait for the detector to reach a set point.
+    pub timeout: Duration,
+    /// Interval between temperature readouts.
+    pub poll_interval: Duration,
+    #[serde(skip)]
+    /// The clock used for waits and timeouts.
+    pub clock: SharedClock,
+}
+
+impl Default for DarkCurrentConfig {
+    fn default() -> Self {
+        Self {
+            exposure: Duration::from_secs(60),
+            bias_exposure: Duration::from_millis(1),
+            tolerance: 0.5,
+            settle_time: Duration::from_secs(30),
+            timeout: Duration::from_secs(600),
+            poll_interval: Duration::from_secs(1),
+            clock: SharedClock::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// A single dark current measurement.
+pub struct DarkCurrentPoint {
+    /// The detector temperature (in degrees C) during the measurement.
+    pub temperature: f32,
+    /// The dark current (in ADU/s).
+    pub rate: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+/// A dark current vs. temperature profile of a detector.
+///
+/// The profile can be serialized and reused to scale dark frames taken
+/// at one temperature to another.
+pub struct DarkCurrentProfile {
+    /// The camera name.
+    pub camera_name: String,
+    /// The measurements, sorted by temperature.
+    pub points: Vec<DarkCurrentPoint>,
+}
+
+impl DarkCurrentProfile {
+    /// Create a new profile from a set of measurements.
+    pub fn new(camera_name: &str, mut points: Vec<DarkCurrentPoint>) -> Self {
+        points.sort_by(|a, b| a.temperature.total_cmp(&b.temperature));
+        Self {
+            camera_name: camera_name.to_string(),
+            points,
+        }
+    }
+
+    /// Get the dark current (in ADU/s) at a given temperature.
+    ///
+    /// The dark current is interpolated exponentially between the two nearest
+    /// measurements, and extrapolated from the two outermost measurements
+    /// outside the profiled range.
+    ///
+    /// # Returns
+    /// `None` if the profile does not contain at least one measurement with
+    /// a positive dark current.
+    pub fn rate_at(&self, temperature: f32) -> Option<f64> {
+        let points: Vec<_> = self.points.iter().filter(|p| p.rate > 0.0).collect();
+        match points.len() {
+            0 => None,
+            1 => Some(points[0].rate),
+            n => {
+                let idx = points
+                    .iter()
+                    .position(|p| p.temperature >= temperature)
+                    .unwrap_or(n - 1)
+                    .clamp(1, n - 1);
+                let (lo, hi) = (points[idx - 1], points[idx]);
+                let span = (hi.temperature - lo.temperature) as f64;
+                if span == 0.0 {
+                    return Some(lo.rate);
+                }
+                let slope = (hi.rate.ln() - lo.rate.ln()) / span;
+                Some((lo.rate.ln() + slope * (temperature - lo.temperature) as f64).exp())
+            }
+        }
+    }
+
+    /// Get the factor by which a dark frame taken at `from` degrees C has to be
+    /// scaled to match the dark current at `to` degrees C.
+    pub fn scale_factor(&self, from: f32, to: f32) -> Option<f64> {
+        Some(self.rate_at(to)? / self.rate_at(from)?)
+    }
+}
+
+/// Wait for the detector temperature to settle at the given set point.
+fn wait_for_temperature(
+    cam: &dyn CameraUnit,
+    setpoint: f32,
+    config: &DarkCurrentConfig,
+) -> Result<f32, Error> {
+    let clock = &config.clock;
+    let start = clock.now();
+    let mut settled_since: Option<Instant> = None;
+    loop {
+        let temperature = cam.get_temperature().ok_or(Error::Message(
+            "Temperature readout not available".to_string(),
+        ))?;
+        if (temperature - setpoint).abs() <= config.tolerance {
+            let since = *settled_since.get_or_insert_with(|| clock.now());
+            if clock.now().duration_since(since) >= config.settle_time {
+                return Ok(temperature);
+            }
+        } else {
+            settled_since = None;
+        }
+        if clock.now().duration_since(start) > config.timeout {
+            return Err(Error::TimedOut);
+        }
+        clock.sleep(config.poll_interval);
+    }
+}
+
+/// Measure the dark current of a detector at a set of temperatures.
+///
+/// For every set point, the cooler is driven to the set point, and a bias and
+/// a dark frame are captured once the temperature has settled. The dark current
+/// is the difference of the mean levels divided by the difference of the exposure
+/// times. The detector must be covered (or the shutter closed) for the duration
+/// of the run. The exposure time of the camera is restored on completion.
+///
+/// # Arguments
+/// - `cam` - The camera to profile.
+/// - `setpoints` - The detector temperatures (in degrees C) to profile.
+/// - `config` - The profiling configuration.
+///
+/// # Returns
+/// The dark current profile, or error.
+pub fn profile_dark_current(
+    cam: &mut dyn CameraUnit,
+    setpoints: &[f32],
+    config: &DarkCurrentConfig,
+) -> Result<DarkCurrentProfile, Error> {
+    if config.exposure <= config.bias_exposure {
+        return Err(Error::InvalidValue(
+            "Dark exposure must be longer than bias exposure".to_string(),
+        ));
+    }
+    let exposure = cam.get_exposure();
+    let res = setpoints
+        .iter()
+        .map(|&setpoint| {
+            cam.set_temperature(setpoint)?;
+            let temperature = wait_for_temperature(cam, setpoint, config)?;
+            cam.set_exposure(config.bias_exposure)?;
+            let bias = mean_level(&cam.capture_image()?);
+            cam.set_exposure(config.exposure)?;
+            let dark = mean_level(&cam.capture_image()?);
+            let rate = (dark - bias) / (config.exposure - config.bias_exposure).as_secs_f64();
+            log::info!(
+                "{}: Dark current at {:.1} C: {:.4} ADU/s",
+                cam.camera_name(),
+                temperature,
+                rate
+            );
+            Ok(DarkCurrentPoint { temperature, rate })
+        })
+        .collect::<Result<Vec<_>, Error>>();
+    cam.set_exposure(exposure)?;
+    Ok(DarkCurrentProfile::new(cam.camera_name(), res?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(temperature: f32, rate: f64) -> DarkCurrentPoint {
+        DarkCurrentPoint { temperature, rate }
+    }
+
+    fn assert_close(a: Option<f64>, b: f64) {
+        let a = a.unwrap();
+        assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn rate_at_interpolates_exponentially() {
+        let profile = DarkCurrentProfile::new(
+            "camera",
+            vec![point(0.0, 0.4), point(-20.0, 0.1), point(-10.0, 0.2)],
+        );
+        assert_eq!(profile.points[0].temperature, -20.0);
+        assert_close(profile.rate_at(-10.0), 0.2);
+        assert_close(profile.rate_at(-15.0), 0.1 * 2f64.sqrt());
+        // Extrapolated from the outermost measurements.
+        assert_close(profile.rate_at(10.0), 0.8);
+        assert_close(profile.rate_at(-30.0), 0.05);
+        assert_close(profile.scale_factor(-20.0, 0.0), 4.0);
+    }
+
+    #[test]
+    fn rate_at_degenerate_profiles() {
+        assert_eq!(DarkCurrentProfile::default().rate_at(0.0), None);
+        let profile = DarkCurrentProfile::new("camera", vec![point(0.0, 0.0), point(-10.0, 0.3)]);
+        assert_eq!(profile.rate_at(20.0), Some(0.3));
+        let profile = DarkCurrentProfile::new("camera", vec![point(0.0, 0.1), point(0.0, 0.3)]);
+        assert_eq!(profile.rate_at(20.0), Some(0.1));
+    }
+}
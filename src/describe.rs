@@ -0,0 +1,71 @@
+use serde_json::{json, Value};
+
+use crate::CameraUnit;
+
+/// Describe the controls of a camera, their ranges and current values as JSON,
+/// so that front-ends can generate settings panels for any driver.
+///
+/// Every control is an object with a `value` field, and `min`/`max` fields where
+/// the range is known. Controls (or range limits) not implemented by the driver
+/// are `null`. Additional controls (see [`CameraUnit::list_controls`]) are listed
+/// under `controls`.
+///
+/// # Arguments
+/// - `cam` - The camera to describe.
+pub fn describe_camera(cam: &dyn CameraUnit) -> Value {
+    let roi = cam.get_roi();
+    let flip = cam.get_flip();
+    let controls: Vec<Value> = cam
+        .list_controls()
+        .iter()
+        .map(|id| json!({ "id": id, "value": cam.get_control(id).ok() }))
+        .collect();
+    json!({
+        "name": cam.camera_name(),
+        "vendor": cam.get_vendor(),
+        "uuid": cam.get_uuid(),
+        "sensor": {
+            "width": cam.get_ccd_width(),
+            "height": cam.get_ccd_height(),
+            "pixel_size": cam.get_pixel_size(),
+        },
+        "exposure": {
+            "value": cam.get_exposure().as_secs_f64(),
+            "min": cam.get_min_exposure().ok().map(|d| d.as_secs_f64()),
+            "max": cam.get_max_exposure().ok().map(|d| d.as_secs_f64()),
+            "unit": "s",
+        },
+        "gain": {
+            "value": cam.get_gain_raw(),
+            "min": cam.get_min_gain().ok(),
+            "max": cam.get_max_gain().ok(),
+            "percent": cam.get_gain(),
+        },
+        "offset": {
+            "value": cam.get_offset(),
+        },
+        "roi": {
+            "value": roi,
+            "min": { "width": 1, "height": 1 },
+            "max": { "width": cam.get_ccd_width(), "height": cam.get_ccd_height() },
+        },
+        "bpp": {
+            "value": cam.get_bpp(),
+        },
+        "flip": {
+            "value": { "x": flip.0, "y": flip.1 },
+        },
+        "shutter_open": {
+            "value": cam.get_shutter_open().ok(),
+        },
+        "temperature": {
+            "value": cam.get_temperature(),
+            "unit": "C",
+        },
+        "cooler": {
+            "value": cam.get_cooler(),
+            "power": cam.get_cooler_power(),
+        },
+        "controls": controls,
+    })
+}
@@ -0,0 +1,253 @@
+/// Implement the [`CameraUnit`](crate::CameraUnit) methods that do not produce frames
+/// or change the exposure and region of interest, by forwarding them to a wrapped camera.
+///
+/// Wrappers implement the remaining methods themselves: `capture_image`,
+/// `download_image`, `download_tile`, `get_partial_readout`, `next_frame`,
+/// `set_frame_callback`, `capture_multi_roi`, `set_exposure` and `set_roi`.
+macro_rules! forward_camera_unit {
+    ($cam:ident) => {
+        fn get_vendor(&self) -> &str {
+            self.$cam.get_vendor()
+        }
+
+        fn get_handle(&self) -> Option<&dyn std::any::Any> {
+            self.$cam.get_handle()
+        }
+
+        fn vendor_command(&mut self, name: &str, payload: &[u8]) -> Result<Vec<u8>, $crate::Error> {
+            self.$cam.vendor_command(name, payload)
+        }
+
+        fn list_controls(&self) -> Vec<$crate::ControlId> {
+            self.$cam.list_controls()
+        }
+
+        fn get_control(
+            &self,
+            id: &$crate::ControlId,
+        ) -> Result<$crate::ControlValue, $crate::Error> {
+            self.$cam.get_control(id)
+        }
+
+        fn set_control(
+            &mut self,
+            id: &$crate::ControlId,
+            value: $crate::ControlValue,
+        ) -> Result<$crate::ControlValue, $crate::Error> {
+            self.$cam.set_control(id, value)
+        }
+
+        fn start_exposure(&self) -> Result<(), $crate::Error> {
+            self.$cam.start_exposure()
+        }
+
+        fn get_hardware_timestamp(&self) -> Option<$crate::HardwareTimestamp> {
+            self.$cam.get_hardware_timestamp()
+        }
+
+        fn image_ready(&self) -> Result<bool, $crate::Error> {
+            self.$cam.image_ready()
+        }
+
+        fn exposure_remaining(&self) -> Result<std::time::Duration, $crate::Error> {
+            self.$cam.exposure_remaining()
+        }
+
+        fn start_stream(&mut self) -> Result<(), $crate::Error> {
+            self.$cam.start_stream()
+        }
+
+        fn stop_stream(&mut self) -> Result<(), $crate::Error> {
+            self.$cam.stop_stream()
+        }
+
+        fn is_streaming(&self) -> bool {
+            self.$cam.is_streaming()
+        }
+
+        fn get_exposure(&self) -> std::time::Duration {
+            self.$cam.get_exposure()
+        }
+
+        fn get_exposure_precise(&self) -> u128 {
+            self.$cam.get_exposure_precise()
+        }
+
+        fn get_gain(&self) -> f32 {
+            self.$cam.get_gain()
+        }
+
+        fn get_gain_raw(&self) -> i64 {
+            self.$cam.get_gain_raw()
+        }
+
+        fn set_gain(&mut self, gain: f32) -> Result<f32, $crate::Error> {
+            self.$cam.set_gain(gain)
+        }
+
+        fn set_gain_raw(&mut self, gain: i64) -> Result<i64, $crate::Error> {
+            self.$cam.set_gain_raw(gain)
+        }
+
+        fn get_offset(&self) -> i32 {
+            self.$cam.get_offset()
+        }
+
+        fn set_offset(&mut self, offset: i32) -> Result<i32, $crate::Error> {
+            self.$cam.set_offset(offset)
+        }
+
+        fn get_min_exposure(&self) -> Result<std::time::Duration, $crate::Error> {
+            self.$cam.get_min_exposure()
+        }
+
+        fn get_max_exposure(&self) -> Result<std::time::Duration, $crate::Error> {
+            self.$cam.get_max_exposure()
+        }
+
+        fn get_min_gain(&self) -> Result<i64, $crate::Error> {
+            self.$cam.get_min_gain()
+        }
+
+        fn get_max_gain(&self) -> Result<i64, $crate::Error> {
+            self.$cam.get_max_gain()
+        }
+
+        fn set_shutter_open(&mut self, open: bool) -> Result<bool, $crate::Error> {
+            self.$cam.set_shutter_open(open)
+        }
+
+        fn get_shutter_open(&self) -> Result<bool, $crate::Error> {
+            self.$cam.get_shutter_open()
+        }
+
+        fn set_bpp(&mut self, bpp: $crate::PixelBpp) -> Result<$crate::PixelBpp, $crate::Error> {
+            self.$cam.set_bpp(bpp)
+        }
+
+        fn get_bpp(&self) -> $crate::PixelBpp {
+            self.$cam.get_bpp()
+        }
+
+        fn set_flip(&mut self, x: bool, y: bool) -> Result<(), $crate::Error> {
+            self.$cam.set_flip(x, y)
+        }
+
+        fn get_flip(&self) -> (bool, bool) {
+            self.$cam.get_flip()
+        }
+
+        fn get_bin_x(&self) -> u32 {
+            self.$cam.get_bin_x()
+        }
+
+        fn get_bin_y(&self) -> u32 {
+            self.$cam.get_bin_y()
+        }
+
+        fn get_roi(&self) -> &$crate::ROI {
+            self.$cam.get_roi()
+        }
+
+        fn set_multi_roi(
+            &mut self,
+            rois: &[$crate::ROI],
+        ) -> Result<Vec<$crate::ROI>, $crate::Error> {
+            self.$cam.set_multi_roi(rois)
+        }
+
+        fn get_multi_roi(&self) -> Option<Vec<$crate::ROI>> {
+            self.$cam.get_multi_roi()
+        }
+
+        fn get_status(&self) -> String {
+            self.$cam.get_status()
+        }
+
+        fn power_off(&mut self) -> Result<(), $crate::Error> {
+            self.$cam.power_off()
+        }
+
+        fn power_on(&mut self) -> Result<(), $crate::Error> {
+            self.$cam.power_on()
+        }
+
+        fn get_power_state(&self) -> Option<$crate::PowerState> {
+            self.$cam.get_power_state()
+        }
+
+        fn camera_ready(&self) -> bool {
+            self.$cam.camera_ready()
+        }
+
+        fn camera_name(&self) -> &str {
+            self.$cam.camera_name()
+        }
+
+        fn cancel_capture(&self) -> Result<(), $crate::Error> {
+            self.$cam.cancel_capture()
+        }
+
+        fn get_uuid(&self) -> Option<&str> {
+            self.$cam.get_uuid()
+        }
+
+        fn is_capturing(&self) -> bool {
+            self.$cam.is_capturing()
+        }
+
+        fn set_temperature(&self, temperature: f32) -> Result<f32, $crate::Error> {
+            self.$cam.set_temperature(temperature)
+        }
+
+        fn get_temperature(&self) -> Option<f32> {
+            self.$cam.get_temperature()
+        }
+
+        fn get_temperature_setpoint(&self) -> Option<f32> {
+            self.$cam.get_temperature_setpoint()
+        }
+
+        fn set_cooler(&self, on: bool) -> Result<(), $crate::Error> {
+            self.$cam.set_cooler(on)
+        }
+
+        fn get_cooler(&self) -> Option<bool> {
+            self.$cam.get_cooler()
+        }
+
+        fn get_cooler_power(&self) -> Option<f32> {
+            self.$cam.get_cooler_power()
+        }
+
+        fn set_cooler_power(&self, power: f32) -> Result<f32, $crate::Error> {
+            self.$cam.set_cooler_power(power)
+        }
+
+        fn get_ccd_width(&self) -> u32 {
+            self.$cam.get_ccd_width()
+        }
+
+        fn get_ccd_height(&self) -> u32 {
+            self.$cam.get_ccd_height()
+        }
+
+        fn get_pixel_size(&self) -> Option<(f32, f32)> {
+            self.$cam.get_pixel_size()
+        }
+
+        fn get_shutter_count(&self) -> Option<u64> {
+            self.$cam.get_shutter_count()
+        }
+
+        fn get_uptime(&self) -> Option<std::time::Duration> {
+            self.$cam.get_uptime()
+        }
+
+        fn get_quirks(&self) -> $crate::Quirks {
+            self.$cam.get_quirks()
+        }
+    };
+}
+
+pub(crate) use forward_camera_unit;
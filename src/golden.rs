@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use fitsio::hdu::{FitsHdu, HduInfo};
+use fitsio::FitsFile;
+
+use crate::Error;
+
+fn open_image(path: &Path) -> Result<(FitsFile, FitsHdu, Vec<usize>), Error> {
+    let map_err = |e: fitsio::errors::Error| Error::FitsError(format!("{}: {}", path.display(), e));
+    let mut fptr = FitsFile::open(path).map_err(map_err)?;
+    let hdu = fptr.hdu(0).map_err(map_err)?;
+    let shape = match &hdu.info {
+        HduInfo::ImageInfo { shape, .. } => shape.clone(),
+        _ => {
+            return Err(Error::InvalidFormat(format!(
+                "{}: Primary HDU is not an image",
+                path.display()
+            )))
+        }
+    };
+    Ok((fptr, hdu, shape))
+}
+
+/// Compare the primary images of two FITS files.
+///
+/// The files are equivalent if the images have the same shape, every pixel differs
+/// by at most `tolerance`, and the selected header keys have the same values.
+/// Unlike a byte comparison, this ignores the header layout, the storage type of
+/// the pixels, and keys that are expected to differ (such as `DATE-OBS`).
+///
+/// # Arguments
+/// - `a` - The path to the first FITS file.
+/// - `b` - The path to the second (reference) FITS file.
+/// - `tolerance` - The maximum allowed absolute difference of a pixel.
+/// - `keys` - The header keys to compare.
+///
+/// # Errors
+/// - [`Error::InvalidValue`] if `tolerance` is negative or NaN.
+/// - [`Error::FitsError`] if a file can not be read.
+/// - [`Error::Message`] describing the first difference found. A NaN pixel only
+///   matches a NaN pixel.
+pub fn compare_fits(a: &Path, b: &Path, tolerance: f64, keys: &[&str]) -> Result<(), Error> {
+    if tolerance.is_nan() || tolerance < 0.0 {
+        return Err(Error::InvalidValue(format!(
+            "Tolerance {} must not be negative",
+            tolerance
+        )));
+    }
+    let (mut fa, ha, shape_a) = open_image(a)?;
+    let (mut fb, hb, shape_b) = open_image(b)?;
+    if shape_a != shape_b {
+        return Err(Error::Message(format!(
+            "Shape is {:?}, expected {:?}",
+            shape_a, shape_b
+        )));
+    }
+    for key in keys {
+        let va: Option<String> = ha.read_key(&mut fa, key).ok();
+        let vb: Option<String> = hb.read_key(&mut fb, key).ok();
+        if va != vb {
+            return Err(Error::Message(format!(
+                "{} is {:?}, expected {:?}",
+                key, va, vb
+            )));
+        }
+    }
+    let da: Vec<f64> = ha
+        .read_image(&mut fa)
+        .map_err(|e| Error::FitsError(format!("{}: {}", a.display(), e)))?;
+    let db: Vec<f64> = hb
+        .read_image(&mut fb)
+        .map_err(|e| Error::FitsError(format!("{}: {}", b.display(), e)))?;
+    // Written so that a NaN difference fails the comparison.
+    let equivalent =
+        |pa: f64, pb: f64| (pa - pb).abs() <= tolerance || (pa.is_nan() && pb.is_nan());
+    match da
+        .iter()
+        .zip(db.iter())
+        .position(|(&pa, &pb)| !equivalent(pa, pb))
+    {
+        Some(idx) => Err(Error::Message(format!(
+            "Pixel {} is {}, expected {} (tolerance {})",
+            idx, da[idx], db[idx], tolerance
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Assert that two FITS files are equivalent (see [`compare_fits`]), for use in
+/// regression tests around saved FITS files.
+///
+/// # Panics
+/// If the files can not be read, or are not equivalent.
+pub fn assert_fits_equivalent(a: &Path, b: &Path, tolerance: f64, keys: &[&str]) {
+    if let Err(e) = compare_fits(a, b, tolerance, keys) {
+        panic!(
+            "FITS files {} and {} differ: {}",
+            a.display(),
+            b.display(),
+            e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use fitsio::images::{ImageDescription, ImageType};
+
+    use super::*;
+
+    fn write(
+        dir: &Path,
+        name: &str,
+        shape: &[usize],
+        data: &[f64],
+        keys: &[(&str, &str)],
+    ) -> PathBuf {
+        let path = dir.join(name);
+        let description = ImageDescription {
+            data_type: ImageType::Double,
+            dimensions: shape,
+        };
+        let mut fptr = FitsFile::create(&path)
+            .with_custom_primary(&description)
+            .open()
+            .unwrap();
+        let hdu = fptr.primary_hdu().unwrap();
+        hdu.write_image(&mut fptr, data).unwrap();
+        for (key, value) in keys {
+            hdu.write_key(&mut fptr, key, *value).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn compare_fits_pixels() {
+        let dir = tempfile::tempdir().unwrap();
+        let reference = write(dir.path(), "ref.fits", &[2, 2], &[1.0, 2.0, 3.0, 4.0], &[]);
+        let close = write(
+            dir.path(),
+            "close.fits",
+            &[2, 2],
+            &[1.0, 2.5, 3.0, 4.0],
+            &[],
+        );
+        assert!(compare_fits(&close, &reference, 0.5, &[]).is_ok());
+        assert!(matches!(
+            compare_fits(&close, &reference, 0.4, &[]),
+            Err(Error::Message(_))
+        ));
+        assert!(matches!(
+            compare_fits(&close, &reference, -1.0, &[]),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            compare_fits(&close, &reference, f64::NAN, &[]),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn compare_fits_nan_pixels() {
+        let dir = tempfile::tempdir().unwrap();
+        let reference = write(dir.path(), "ref.fits", &[1, 2], &[1.0, 2.0], &[]);
+        let nan = write(dir.path(), "nan.fits", &[1, 2], &[1.0, f64::NAN], &[]);
+        let nan2 = write(dir.path(), "nan2.fits", &[1, 2], &[1.0, f64::NAN], &[]);
+        assert!(compare_fits(&nan, &reference, 1e9, &[]).is_err());
+        assert!(compare_fits(&reference, &nan, 1e9, &[]).is_err());
+        assert!(compare_fits(&nan, &nan2, 0.0, &[]).is_ok());
+    }
+
+    #[test]
+    fn compare_fits_shape_and_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = [0.0; 4];
+        let reference = write(dir.path(), "ref.fits", &[2, 2], &data, &[("CAMERA", "a")]);
+        let same = write(dir.path(), "same.fits", &[2, 2], &data, &[("CAMERA", "a")]);
+        let shape = write(dir.path(), "shape.fits", &[1, 4], &data, &[("CAMERA", "a")]);
+        let other = write(dir.path(), "other.fits", &[2, 2], &data, &[("CAMERA", "b")]);
+        let missing = write(dir.path(), "missing.fits", &[2, 2], &data, &[]);
+        assert!(compare_fits(&same, &reference, 0.0, &["CAMERA"]).is_ok());
+        assert!(compare_fits(&shape, &reference, 0.0, &[]).is_err());
+        assert!(compare_fits(&other, &reference, 0.0, &["CAMERA"]).is_err());
+        assert!(compare_fits(&other, &reference, 0.0, &[]).is_ok());
+        assert!(compare_fits(&missing, &reference, 0.0, &["CAMERA"]).is_err());
+    }
+}
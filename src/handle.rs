@@ -0,0 +1,33 @@
+use std::any::Any;
+
+use crate::CameraUnit;
+
+/// Contract for types exposed through [`CameraUnit::get_handle`].
+///
+/// Drivers implement this for their handle type, so that consumers that need raw
+/// SDK access can retrieve it with [`CameraUnitExt::handle_as`].
+pub trait HandleToken: Any {
+    /// The vendor of the driver exposing the handle, as returned by
+    /// [`CameraUnit::get_vendor`].
+    const VENDOR: &'static str;
+}
+
+/// Extension methods for [`CameraUnit`].
+pub trait CameraUnitExt {
+    /// Get the internal camera handle (see [`CameraUnit::get_handle`]) as a concrete type.
+    ///
+    /// # Returns
+    /// `None` if the camera does not expose a handle, the vendor of the camera does
+    /// not match [`HandleToken::VENDOR`], or the handle is of a different type.
+    fn handle_as<T: HandleToken>(&self) -> Option<&T>;
+}
+
+impl<C: CameraUnit + ?Sized> CameraUnitExt for C {
+    fn handle_as<T: HandleToken>(&self) -> Option<&T> {
+        if self.get_vendor() != T::VENDOR {
+            return None;
+        }
+        self.get_handle()
+            .and_then(|handle: &dyn Any| handle.downcast_ref::<T>())
+    }
+}
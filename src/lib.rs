@@ -17,7 +17,7 @@ You can use `cameraunit` to:
 Add this to your `Cargo.toml`:
 ```toml
 [dependencies]
-cameraunit = "6.0"
+cameraunit = "7.0"
 ```
 and this to your source code:
 ```no_run
@@ -52,7 +52,10 @@ Ideally, the crate implementing the camera interface should
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::sync::Arc;
-use std::{fmt::Display, time::Duration};
+use std::{
+    fmt::Display,
+    time::{Duration, SystemTime},
+};
 use thiserror::Error;
 
 pub use serialimage::{
@@ -60,9 +63,73 @@ pub use serialimage::{
     Primitive, SerialImageBuffer,
 };
 
+mod advisor;
+#[cfg(feature = "async")]
+mod asynchronous;
+mod cadence;
+mod capture;
+#[cfg(feature = "cli")]
+pub mod cli_args;
+mod clock;
+mod config;
+mod control;
+mod cooling;
+mod darkcurrent;
+mod describe;
+mod forward;
+mod golden;
+mod handle;
+mod monitor;
+mod observation;
+mod optics;
+mod photometry;
+mod pipeline;
+mod quirks;
+mod schedule;
+mod sequence;
+mod stats;
+mod strict;
+mod table;
+mod tracking;
+
+pub use advisor::{
+    sweep_settings, ExposureAdvice, ExposureAdvisor, ExposureReport, SweepReport, SweepResult,
+};
+#[cfg(feature = "async")]
+pub use asynchronous::{AsyncCamera, AsyncCameraInfo, AsyncCameraUnit, AsyncInfo};
+pub use cadence::{
+    check_data_rate, estimate_data_rate, measure_write_throughput, CadenceController,
+    CadencePolicy, DataRatePolicy, DutyCyclePolicy, FrameRecord,
+};
+pub use capture::{
+    meter_exposure, record_exposure, CaptureOptions, ExposureSnapshot, FrameCounter, FrameTiming,
+};
+pub use clock::{Clock, ClockJump, ClockJumpDetector, SharedClock, SystemClock, TestClock};
+pub use config::{CameraConfig, SettingsStore};
+pub use control::CameraControlHandle;
+pub use cooling::{graceful_shutdown, CoolerGuard, CoolingRamp};
+pub use darkcurrent::{
+    profile_dark_current, DarkCurrentConfig, DarkCurrentPoint, DarkCurrentProfile,
+};
+pub use describe::describe_camera;
+pub use golden::{assert_fits_equivalent, compare_fits};
+pub use handle::{CameraUnitExt, HandleToken};
+pub use monitor::{OverexposureGuard, StuckFrameDetector, TransientDetector, TransientEvent};
+pub use observation::ObservationInfo;
+pub use optics::{field_of_view, pixel_scale, PlateScale};
+pub use photometry::{AperturePhotometry, FluxMeasurement, LightCurve, LightCurvePoint};
+pub use pipeline::{FrameProcessor, ProcessingCamera};
+pub use quirks::Quirks;
+pub use schedule::{DayNight, DayNightScheduler, ExposureProfile};
+pub use sequence::{Sequence, SequenceHandler, SequenceStep, SequenceTarget};
+pub use strict::StrictCamera;
+pub use table::{ColumnData, TableColumn, TableData};
+pub use tracking::{FocusZoom, TrackingRoi};
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Hash)]
 /// This structure defines a region of interest.
-/// The region of interest is defined in the un-binned pixel space.
+/// The origin and size of the region of interest are defined in the binned pixel
+/// space (see [`ROI::sensor_rect`] for the un-binned sensor coordinates).
 pub struct ROI {
     /// The minimum X coordinate (in binned pixel space).
     pub x_min: u32,
@@ -76,6 +143,205 @@ pub struct ROI {
     pub bin_x: u32,
     /// The Y binning factor.
     pub bin_y: u32,
+    /// The X decimation factor: one in every `skip_x` columns is read out.
+    /// Unlike binning, skipped pixels do not contribute to the image.
+    #[serde(default = "no_skip")]
+    pub skip_x: u32,
+    /// The Y decimation factor: one in every `skip_y` rows is read out.
+    /// Unlike binning, skipped pixels do not contribute to the image.
+    #[serde(default = "no_skip")]
+    pub skip_y: u32,
+}
+
+fn no_skip() -> u32 {
+    1
+}
+
+impl ROI {
+    /// Create a region of interest from a rectangle in binned pixel space.
+    ///
+    /// # Arguments
+    /// - `rect` - The rectangle.
+    /// - `bin_x` - The X binning factor.
+    /// - `bin_y` - The Y binning factor.
+    pub fn from_rect(rect: image::math::Rect, bin_x: u32, bin_y: u32) -> Self {
+        Self {
+            x_min: rect.x,
+            y_min: rect.y,
+            width: rect.width,
+            height: rect.height,
+            bin_x,
+            bin_y,
+            skip_x: 1,
+            skip_y: 1,
+        }
+    }
+
+    /// Get the rectangle covered by the region of interest in un-binned sensor pixels.
+    ///
+    /// Coordinates that do not fit in `u32` saturate at `u32::MAX` (see
+    /// [`ROI::checked_sensor_rect`]).
+    pub fn sensor_rect(&self) -> image::math::Rect {
+        let (bin_x, bin_y) = (self.bin_x.max(1), self.bin_y.max(1));
+        image::math::Rect {
+            x: self.x_min.saturating_mul(bin_x),
+            y: self.y_min.saturating_mul(bin_y),
+            width: self
+                .width
+                .saturating_mul(bin_x)
+                .saturating_mul(self.skip_x.max(1)),
+            height: self
+                .height
+                .saturating_mul(bin_y)
+                .saturating_mul(self.skip_y.max(1)),
+        }
+    }
+
+    /// Get the rectangle covered by the region of interest in un-binned sensor pixels.
+    ///
+    /// # Returns
+    /// The rectangle, or `None` if the rectangle (including its far edges) does not
+    /// fit in `u32` sensor coordinates.
+    pub fn checked_sensor_rect(&self) -> Option<image::math::Rect> {
+        let (bin_x, bin_y) = (self.bin_x.max(1), self.bin_y.max(1));
+        let rect = image::math::Rect {
+            x: self.x_min.checked_mul(bin_x)?,
+            y: self.y_min.checked_mul(bin_y)?,
+            width: self
+                .width
+                .checked_mul(bin_x)?
+                .checked_mul(self.skip_x.max(1))?,
+            height: self
+                .height
+                .checked_mul(bin_y)?
+                .checked_mul(self.skip_y.max(1))?,
+        };
+        rect.x.checked_add(rect.width)?;
+        rect.y.checked_add(rect.height)?;
+        Some(rect)
+    }
+
+    /// Check that an image is consistent with this region of interest.
+    ///
+    /// The image dimensions must match the size of the region of interest, and the
+    /// binning and origin in the image metadata (if present) must match the binning
+    /// and origin of the region of interest. Like the region of interest, the origin
+    /// in the metadata (`img_left`, `img_top`) is in binned pixels. This is intended to catch driver bugs
+    /// at the boundary instead of in downstream analysis.
+    ///
+    /// # Errors
+    /// [`Error::RoiMismatch`] describing the first inconsistency found.
+    pub fn check_image(&self, img: &DynamicSerialImage) -> Result<(), Error> {
+        let mismatch = |what: &str, expected: u32, actual: u32| {
+            Err(Error::RoiMismatch(format!(
+                "{} is {}, expected {}",
+                what, actual, expected
+            )))
+        };
+        if img.width() != self.width as usize {
+            return mismatch("Width", self.width, img.width() as u32);
+        }
+        if img.height() != self.height as usize {
+            return mismatch("Height", self.height, img.height() as u32);
+        }
+        if let Some(meta) = img.get_metadata() {
+            if meta.bin_x != self.bin_x {
+                return mismatch("X binning", self.bin_x, meta.bin_x);
+            }
+            if meta.bin_y != self.bin_y {
+                return mismatch("Y binning", self.bin_y, meta.bin_y);
+            }
+            if meta.img_left != self.x_min {
+                return mismatch("X origin", self.x_min, meta.img_left);
+            }
+            if meta.img_top != self.y_min {
+                return mismatch("Y origin", self.y_min, meta.img_top);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse a region of interest from a string of the form
+/// `x,y,width,height[,bin_x,bin_y[,skip_x,skip_y]]`, in binned pixel space.
+/// Omitted binning and decimation factors default to `1`.
+///
+/// The parser does not panic, and rejects input that can not describe a valid
+/// region of interest, so it is safe to use on untrusted input.
+///
+/// # Errors
+/// - [`Error::InvalidFormat`] if the string does not have 4, 6 or 8 comma-separated
+///   unsigned integers.
+/// - [`Error::InvalidValue`] if a binning or decimation factor is zero, or the
+///   region of interest does not fit in `u32` sensor coordinates.
+pub fn parse_roi_str(s: &str) -> Result<ROI, Error> {
+    let mut vals = [0u32, 0, 0, 0, 1, 1, 1, 1];
+    let mut count = 0;
+    for field in s.split(',') {
+        let val = vals
+            .get_mut(count)
+            .ok_or(Error::InvalidFormat("ROI: Too many fields".to_string()))?;
+        *val = field.trim().parse().map_err(|_| {
+            Error::InvalidFormat(format!("ROI: Field {} is not an unsigned integer", count))
+        })?;
+        count += 1;
+    }
+    if !matches!(count, 4 | 6 | 8) {
+        return Err(Error::InvalidFormat(format!(
+            "ROI: Expected 4, 6 or 8 fields, got {}",
+            count
+        )));
+    }
+    let [x_min, y_min, width, height, bin_x, bin_y, skip_x, skip_y] = vals;
+    if bin_x == 0 || bin_y == 0 || skip_x == 0 || skip_y == 0 {
+        return Err(Error::InvalidValue(
+            "ROI: Binning and decimation factors must be positive".to_string(),
+        ));
+    }
+    let roi = ROI {
+        x_min,
+        y_min,
+        width,
+        height,
+        bin_x,
+        bin_y,
+        skip_x,
+        skip_y,
+    };
+    match roi.checked_sensor_rect() {
+        Some(_) => Ok(roi),
+        None => Err(Error::InvalidValue(
+            "ROI: Region exceeds the sensor coordinate range".to_string(),
+        )),
+    }
+}
+
+impl std::str::FromStr for ROI {
+    type Err = Error;
+
+    /// Parse a region of interest (see [`parse_roi_str`]).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_roi_str(s)
+    }
+}
+
+impl From<ROI> for image::math::Rect {
+    /// Convert the region of interest to a rectangle in binned pixel space.
+    fn from(roi: ROI) -> Self {
+        image::math::Rect {
+            x: roi.x_min,
+            y: roi.y_min,
+            width: roi.width,
+            height: roi.height,
+        }
+    }
+}
+
+impl From<ROI> for (u32, u32, u32, u32) {
+    /// Convert the region of interest to a `(x, y, width, height)` tuple in binned pixel space.
+    fn from(roi: ROI) -> Self {
+        (roi.x_min, roi.y_min, roi.width, roi.height)
+    }
 }
 
 impl Display for ROI {
@@ -84,12 +350,31 @@ impl Display for ROI {
             f,
             "ROI: Origin = ({}, {}), Image Size = ({} x {}), Bin = ({}, {})",
             self.x_min, self.y_min, self.width, self.height, self.bin_x, self.bin_y
-        )
+        )?;
+        if self.skip_x > 1 || self.skip_y > 1 {
+            write!(f, ", Skip = ({}, {})", self.skip_x, self.skip_y)?;
+        }
+        Ok(())
     }
 }
 
+#[derive(Clone, Debug)]
+/// An image of one region of a multi-window readout.
+pub struct RoiImage {
+    /// The index of the region of interest in the list passed to [`CameraUnit::set_multi_roi`].
+    pub index: usize,
+    /// The region of interest.
+    pub roi: ROI,
+    /// The image.
+    pub image: DynamicSerialImage,
+}
+
 /// A trait object for a camera unit.
 pub type AnyCameraUnit = Box<dyn CameraUnit>;
+/// A callback receiving the frames of a stream (see [`CameraUnit::set_frame_callback`]).
+pub type FrameCallback = Box<dyn FnMut(DynamicSerialImage) + Send>;
+/// A trait object for a thread-safe camera unit.
+pub type AnySyncCameraUnit = Box<dyn SyncCameraUnit>;
 /// A trait object for a camera info.
 pub type AnyCameraInfo = Arc<Box<dyn CameraInfo>>;
 
@@ -108,6 +393,19 @@ pub trait CameraDriver {
     ) -> Result<(AnyCameraUnit, AnyCameraInfo), Error>;
     /// Connect to the first available device.
     fn connect_first_device(&mut self) -> Result<(AnyCameraUnit, AnyCameraInfo), Error>;
+    /// Connect to all sensors of a device with more than one sensor (e.g. a main
+    /// and a guide sensor in one body). The sensors share a single camera info
+    /// object, and are returned in the order of [`CameraDescriptor::sensor`].
+    ///
+    /// Defaults to connecting to the sensor of `descriptor` with
+    /// [`CameraDriver::connect_device`] if unimplemented.
+    fn connect_device_sensors(
+        &mut self,
+        descriptor: &CameraDescriptor,
+    ) -> Result<(Vec<AnyCameraUnit>, AnyCameraInfo), Error> {
+        self.connect_device(descriptor)
+            .map(|(cam, info)| (vec![cam], info))
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -117,6 +415,28 @@ pub struct CameraDescriptor {
     pub id: usize,
     /// The camera name.
     pub name: String,
+    /// The index of the sensor, for devices with more than one sensor.
+    /// Devices with a single sensor use `0`.
+    #[serde(default)]
+    pub sensor: usize,
+    /// The accessories integrated into the device.
+    #[serde(default)]
+    pub accessories: Vec<Accessory>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+/// An accessory integrated into a camera device.
+pub enum Accessory {
+    /// A filter wheel.
+    FilterWheel,
+    /// An autoguider (ST-4) port.
+    GuidePort,
+    /// A GPS receiver for time stamping.
+    Gps,
+    /// A focuser.
+    Focuser,
+    /// A vendor-specific accessory.
+    Other(String),
 }
 
 /// Trait for obtaining camera information and cancelling any ongoing image capture.
@@ -127,6 +447,13 @@ pub trait CameraInfo: Send + Sync {
     /// Check if camera is ready.
     fn camera_ready(&self) -> bool;
 
+    /// Get the accessories integrated into the camera.
+    ///
+    /// Defaults to an empty list if unimplemented.
+    fn get_accessories(&self) -> Vec<Accessory> {
+        Vec::new()
+    }
+
     /// Get the camera name.
     fn camera_name(&self) -> &str;
 
@@ -197,6 +524,44 @@ pub trait CameraInfo: Send + Sync {
     fn get_pixel_size(&self) -> Option<(f32, f32)> {
         None
     }
+
+    /// Get the number of mechanical shutter actuations over the lifetime of the camera.
+    ///
+    /// Defaults to `None` if unimplemented/not available.
+    fn get_shutter_count(&self) -> Option<u64> {
+        None
+    }
+
+    /// Get the time since the camera was powered on.
+    ///
+    /// Defaults to `None` if unimplemented/not available.
+    fn get_uptime(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Get the currently set exposure time, without exclusive access to the camera.
+    ///
+    /// Named differently from [`CameraUnit::get_exposure`] so that calls on types
+    /// implementing both traits are not ambiguous.
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn try_get_exposure(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Get the current gain (in percentage units), without exclusive access to the camera.
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn try_get_gain(&self) -> Option<f32> {
+        None
+    }
+
+    /// Get the current region of interest, without exclusive access to the camera.
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn try_get_roi(&self) -> Option<ROI> {
+        None
+    }
 }
 
 /// Trait for controlling the camera. This trait is intended to be applied to a
@@ -209,24 +574,114 @@ pub trait CameraUnit: Send {
 
     /// Get a handle to the internal camera. This is intended to be used for
     /// development purposes, as (presumably FFI and unsafe) internal calls
-    /// are abstracted away from the user.
+    /// are abstracted away from the user. The handle type should implement
+    /// [`HandleToken`], so that it can be retrieved with [`CameraUnitExt::handle_as`].
     ///
     /// Defaults to `None` if unimplemented.
     fn get_handle(&self) -> Option<&dyn Any> {
         None
     }
 
+    /// Send a vendor-specific command to the camera, for features that are not
+    /// covered by this trait. Command names and payload formats are defined by
+    /// the driver.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the command.
+    /// - `payload` - The command payload.
+    ///
+    /// # Returns
+    /// The response of the camera, or error. Drivers should raise an
+    /// [`Error::InvalidValue`] for unknown commands.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn vendor_command(&mut self, _name: &str, _payload: &[u8]) -> Result<Vec<u8>, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// List the controls supported by the camera through [`CameraUnit::get_control`]
+    /// and [`CameraUnit::set_control`].
+    ///
+    /// Defaults to an empty list if unimplemented.
+    fn list_controls(&self) -> Vec<ControlId> {
+        Vec::new()
+    }
+
+    /// Get the value of a control, for settings that are not covered by this trait.
+    ///
+    /// # Arguments
+    /// - `id` - The control.
+    ///
+    /// # Errors
+    /// Drivers should raise an [`Error::InvalidControlType`] for unsupported controls.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn get_control(&self, _id: &ControlId) -> Result<ControlValue, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Set the value of a control, for settings that are not covered by this trait.
+    ///
+    /// # Arguments
+    /// - `id` - The control.
+    /// - `value` - The value.
+    ///
+    /// # Returns
+    /// The value that was set, or error.
+    ///
+    /// # Errors
+    /// - Drivers should raise an [`Error::InvalidControlType`] for unsupported controls.
+    /// - Drivers should raise an [`Error::InvalidValue`] for values of the wrong type
+    ///   or out of range.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn set_control(
+        &mut self,
+        _id: &ControlId,
+        _value: ControlValue,
+    ) -> Result<ControlValue, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
     /// Capture an image.
     ///
     /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
     fn capture_image(&self) -> Result<DynamicSerialImage, Error>;
 
     /// Start an exposure and return. This function does NOT block.
+    ///
+    /// Note:
+    /// - The metadata of the image returned by [`CameraUnit::download_image`] must
+    ///   reflect the settings in effect when this function was called, even if the
+    ///   settings are changed during the exposure or download. Drivers that can not
+    ///   guarantee this may use [`ExposureSnapshot`].
     fn start_exposure(&self) -> Result<(), Error>;
 
     /// Download the image captured in [`CameraUnit::start_exposure`].
     fn download_image(&self) -> Result<DynamicSerialImage, Error>;
 
+    /// Download a region of the image captured in [`CameraUnit::start_exposure`],
+    /// for cameras that support region readout after the exposure. This allows fast
+    /// checks (e.g. centroids of guide stars) without transferring the full frame.
+    ///
+    /// # Arguments
+    /// - `tile` - The region to download, relative to the current region of interest.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn download_tile(&self, _tile: &ROI) -> Result<DynamicSerialImage, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Get the hardware timestamp of the last downloaded image, for cameras with
+    /// on-board timestamping. Drivers that implement this should prefer the hardware
+    /// timestamp over the host time in the image metadata
+    /// (see [`HardwareTimestamp::update_metadata`]).
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn get_hardware_timestamp(&self) -> Option<HardwareTimestamp> {
+        None
+    }
+
     /// Get exposure status. This function is useful for checking if a
     /// non-blocking exposure has finished running.
     fn image_ready(&self) -> Result<bool, Error>;
@@ -234,11 +689,73 @@ pub trait CameraUnit: Send {
     /// Get the remaining exposure time.
     fn exposure_remaining(&self) -> Result<Duration, Error>;
 
+    /// Get a preview of the exposure started in [`CameraUnit::start_exposure`]
+    /// while it is still running. This is intended for cameras that support
+    /// non-destructive readout, so that a developing histogram or preview can
+    /// be shown during very long exposures. The exposure is not interrupted.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn get_partial_readout(&self) -> Result<DynamicSerialImage, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Start continuous (video) readout, for cameras that can deliver frames
+    /// without the [`CameraUnit::start_exposure`]/[`CameraUnit::download_image`]
+    /// round trip. Frames are exposed with the current settings, and are obtained
+    /// with [`CameraUnit::next_frame`] or delivered to the callback registered with
+    /// [`CameraUnit::set_frame_callback`].
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn start_stream(&mut self) -> Result<(), Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Stop continuous readout started with [`CameraUnit::start_stream`].
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn stop_stream(&mut self) -> Result<(), Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Check if continuous readout is running.
+    ///
+    /// Defaults to `false` if unimplemented.
+    fn is_streaming(&self) -> bool {
+        false
+    }
+
+    /// Get the next frame of the stream started with [`CameraUnit::start_stream`].
+    ///
+    /// # Arguments
+    /// - `timeout` - The maximum time to wait for a frame.
+    ///
+    /// # Errors
+    /// [`Error::TimedOut`] if no frame arrived within `timeout`.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn next_frame(&self, _timeout: Duration) -> Result<DynamicSerialImage, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Register a callback that receives every frame of the stream started with
+    /// [`CameraUnit::start_stream`], replacing any previously registered callback.
+    /// Frames delivered to the callback are not returned by [`CameraUnit::next_frame`].
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn set_frame_callback(&mut self, _callback: FrameCallback) -> Result<(), Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
     /// Set the exposure time.
     ///
     /// # Arguments
     /// - `exposure` - The exposure time as a [`Duration`].
     ///
+    /// Note:
+    /// - Drivers should not round the exposure time to a coarser unit (e.g. microseconds)
+    ///   than supported by the camera, so that the exposure time round-trips at up to
+    ///   nanosecond precision.
+    ///
     /// # Returns
     /// The exposure time that was set, or error.
     fn set_exposure(&mut self, _exposure: Duration) -> Result<Duration, Error>;
@@ -249,6 +766,14 @@ pub trait CameraUnit: Send {
     /// - The exposure time as a [`Duration`].
     fn get_exposure(&self) -> Duration;
 
+    /// Get the currently set exposure time in picoseconds, for detectors that support
+    /// exposure times finer than the nanosecond resolution of [`Duration`].
+    ///
+    /// Defaults to the exposure time from [`CameraUnit::get_exposure`] if unimplemented.
+    fn get_exposure_precise(&self) -> u128 {
+        self.get_exposure().as_nanos() * 1000
+    }
+
     /// Get the current gain (in percentage units).
     ///
     /// Defaults to `0.0` if unimplemented.
@@ -341,6 +866,8 @@ pub trait CameraUnit: Send {
     /// Note:
     /// - The region of interest is defined in the binned pixel space.
     /// - Setting all values to `0` will set the ROI to the full detector size.
+    /// - Cameras that do not support decimation must reject a region of interest
+    ///   with `skip_x` or `skip_y` other than `1`.
     ///
     ///
     /// # Returns
@@ -393,6 +920,37 @@ pub trait CameraUnit: Send {
     /// - The region of interest.
     fn get_roi(&self) -> &ROI;
 
+    /// Set multiple regions of interest that are read out together, for cameras
+    /// that support multi-window readout.
+    ///
+    /// # Arguments
+    /// - `rois` - The regions of interest. All regions must have the same binning.
+    ///
+    /// # Returns
+    /// The regions of interest that were set, or error.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn set_multi_roi(&mut self, _rois: &[ROI]) -> Result<Vec<ROI>, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Get the regions of interest set by [`CameraUnit::set_multi_roi`].
+    ///
+    /// Defaults to `None` if unimplemented, or if multi-window readout is not active.
+    fn get_multi_roi(&self) -> Option<Vec<ROI>> {
+        None
+    }
+
+    /// Capture an image with the regions of interest set by [`CameraUnit::set_multi_roi`].
+    ///
+    /// # Returns
+    /// One image per region of interest, in the order the regions were set, or error.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn capture_multi_roi(&self) -> Result<Vec<RoiImage>, Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
     /// Get the current operational status of the camera.
     ///
     /// Defaults to `"Not implemented"` if unimplemented.
@@ -400,6 +958,29 @@ pub trait CameraUnit: Send {
         "Not implemented".to_string()
     }
 
+    /// Power off the camera, for cameras that support software power control.
+    /// The camera settings are retained, and restored by [`CameraUnit::power_on`].
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn power_off(&mut self) -> Result<(), Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Power on the camera, and restore the settings in effect when
+    /// [`CameraUnit::power_off`] was called.
+    ///
+    /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
+    fn power_on(&mut self) -> Result<(), Error> {
+        Err(Error::Message("Not implemented".to_string()))
+    }
+
+    /// Get the power state of the camera.
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn get_power_state(&self) -> Option<PowerState> {
+        None
+    }
+
     /// Check if camera is ready.
     fn camera_ready(&self) -> bool;
 
@@ -433,6 +1014,13 @@ pub trait CameraUnit: Send {
         None
     }
 
+    /// Get the target detector temperature last set with [`CameraUnit::set_temperature`].
+    ///
+    /// Defaults to `None` if unimplemented.
+    fn get_temperature_setpoint(&self) -> Option<f32> {
+        None
+    }
+
     /// Enable/disable cooler.
     ///
     /// Raises a `Message` with the message `"Not implemented"` if unimplemented.
@@ -473,6 +1061,58 @@ pub trait CameraUnit: Send {
     fn get_pixel_size(&self) -> Option<(f32, f32)> {
         None
     }
+
+    /// Get the number of mechanical shutter actuations over the lifetime of the camera.
+    ///
+    /// Defaults to `None` if unimplemented/not available.
+    fn get_shutter_count(&self) -> Option<u64> {
+        None
+    }
+
+    /// Get the time since the camera was powered on.
+    ///
+    /// Defaults to `None` if unimplemented/not available.
+    fn get_uptime(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Get the known workarounds (settle times, region of interest alignment) for
+    /// the camera.
+    ///
+    /// Defaults to [`Quirks::default`] (no workarounds) if unimplemented.
+    fn get_quirks(&self) -> Quirks {
+        Quirks::default()
+    }
+}
+
+/// A camera unit that can be shared between threads.
+///
+/// [`CameraUnit`] only requires `Send`, since most camera SDKs are not thread-safe.
+/// Drivers whose SDKs are thread-safe opt in by making their camera unit `Sync` and
+/// implementing this trait. The `&self` methods (capture, download, status) can
+/// then be called concurrently, e.g. through an `Arc`, without a `Mutex`.
+///
+/// The trait is not implemented automatically for every `Sync` camera unit, since
+/// a type being `Sync` does not guarantee that concurrent calls into the SDK are
+/// safe. Implement it explicitly once the SDK has been verified to be thread-safe:
+///
+/// ```ignore
+/// impl SyncCameraUnit for MyCamera {
+///     fn as_camera_unit(&self) -> &dyn CameraUnit {
+///         self
+///     }
+///
+///     fn as_camera_unit_mut(&mut self) -> &mut dyn CameraUnit {
+///         self
+///     }
+/// }
+/// ```
+pub trait SyncCameraUnit: CameraUnit + Sync {
+    /// Get the camera unit as a [`CameraUnit`] trait object.
+    fn as_camera_unit(&self) -> &dyn CameraUnit;
+
+    /// Get the camera unit as a mutable [`CameraUnit`] trait object.
+    fn as_camera_unit_mut(&mut self) -> &mut dyn CameraUnit;
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -514,6 +1154,103 @@ impl From<u32> for PixelBpp {
     }
 }
 
+impl PixelBpp {
+    /// Get the largest pixel value at this bit depth, capped at the 16-bit full
+    /// scale of the luminance data used for frame statistics.
+    pub fn full_scale(&self) -> f64 {
+        ((1u32 << (*self as u32).min(16)) - 1) as f64
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Power state of a camera.
+pub enum PowerState {
+    /// The camera is powered on.
+    On,
+    /// The camera is powered off.
+    Off,
+    /// The camera is transitioning between power states.
+    Transitioning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Identifier of a camera control (see [`CameraUnit::get_control`]).
+pub enum ControlId {
+    /// USB bandwidth limit.
+    UsbTraffic,
+    /// High speed readout mode.
+    HighSpeedMode,
+    /// Fixed pattern noise adjustment.
+    PatternAdjust,
+    /// Anti-dew heater.
+    AntiDewHeater,
+    /// Cooling fan.
+    Fan,
+    /// Gamma correction.
+    Gamma,
+    /// A vendor-specific control, named by the driver.
+    Custom(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Value of a camera control (see [`CameraUnit::get_control`]).
+pub enum ControlValue {
+    /// A switch.
+    Bool(bool),
+    /// An integer value.
+    Int(i64),
+    /// A floating point value.
+    Float(f64),
+    /// A named mode or text value.
+    Text(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Source of the clock used to timestamp an image.
+pub enum ClockSource {
+    /// The host computer clock.
+    Host,
+    /// The free-running camera clock.
+    Camera,
+    /// A GPS-disciplined clock.
+    Gps,
+}
+
+impl ClockSource {
+    /// Get the name of the clock source as recorded in the `CLOCKSRC` FITS key.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClockSource::Host => "HOST",
+            ClockSource::Camera => "CAMERA",
+            ClockSource::Gps => "GPS",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Timestamp of the start of an exposure, as reported by the camera hardware.
+pub struct HardwareTimestamp {
+    /// The timestamp of the start of the exposure.
+    pub timestamp: SystemTime,
+    /// The clock source of the timestamp.
+    pub source: ClockSource,
+    /// The estimated offset of the clock source from the host clock (in seconds),
+    /// if known. Positive values indicate the clock source is ahead of the host.
+    pub offset: Option<f64>,
+}
+
+impl HardwareTimestamp {
+    /// Set the timestamp in the image metadata to the hardware timestamp, and record
+    /// the clock source (`CLOCKSRC`) and offset estimate (`CLOCKOFF`, in seconds).
+    pub fn update_metadata(&self, meta: &mut ImageMetaData) {
+        meta.timestamp = self.timestamp;
+        meta.add_extended_attrib("CLOCKSRC", self.source.as_str());
+        if let Some(offset) = self.offset {
+            meta.add_extended_attrib("CLOCKOFF", &format!("{:.9}", offset));
+        }
+    }
+}
+
 #[derive(Error, Debug, PartialEq, Serialize, Deserialize)]
 /// Errors returned by camera operations.
 pub enum Error {
@@ -580,4 +1317,290 @@ pub enum Error {
     /// Exposure not started.
     #[error("Exposure not started.")]
     ExposureNotStarted,
+    /// FITS I/O error.
+    #[error("FITS error: {0}")]
+    FitsError(String),
+    /// Driver repeatedly returned the same frame.
+    #[error("Stuck frame: {0} identical frames in a row")]
+    StuckFrame(usize),
+    /// Frames overexposed.
+    #[error("Overexposed: mean level {0:.3} of full scale")]
+    Overexposed(f64),
+    /// Image inconsistent with region of interest.
+    #[error("Image does not match ROI: {0}")]
+    RoiMismatch(String),
+    /// Error with a driver-provided recovery hint.
+    #[error("{0}")]
+    WithHint(Box<Error>, RecoveryHint),
+}
+
+impl Error {
+    /// Attach a recovery hint to the error, overriding the default hint
+    /// returned by [`Error::recovery_hint`].
+    pub fn with_hint(self, hint: RecoveryHint) -> Self {
+        match self {
+            Error::WithHint(err, _) => Error::WithHint(err, hint),
+            err => Error::WithHint(Box::new(err), hint),
+        }
+    }
+
+    /// Get the underlying error, without any recovery hint.
+    ///
+    /// Match on the root error when checking for a kind of error, since drivers
+    /// may attach a hint to any error using [`Error::with_hint`].
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::WithHint(err, _) => err.root(),
+            err => err,
+        }
+    }
+
+    /// Get the stable numeric code of the error.
+    ///
+    /// Codes are never reassigned, so they can be matched by logs, RPC layers and
+    /// non-Rust consumers without parsing error messages. Errors with a recovery
+    /// hint have the code of the underlying error.
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Message(_) => 1,
+            Error::InvalidIndex(_) => 2,
+            Error::InvalidId(_) => 3,
+            Error::InvalidControlType(_) => 4,
+            Error::NoCamerasAvailable => 5,
+            Error::CameraClosed => 6,
+            Error::CameraRemoved => 7,
+            Error::InvalidPath(_) => 8,
+            Error::InvalidFormat(_) => 9,
+            Error::InvalidSize(_) => 10,
+            Error::InvalidImageType(_) => 11,
+            Error::TimedOut => 12,
+            Error::InvalidSequence => 13,
+            Error::BufferTooSmall(_) => 14,
+            Error::ExposureInProgress => 15,
+            Error::GeneralError(_) => 16,
+            Error::InvalidMode(_) => 17,
+            Error::ExposureFailed(_) => 18,
+            Error::InvalidValue(_) => 19,
+            Error::OutOfBounds(_) => 20,
+            Error::ExposureNotStarted => 21,
+            Error::FitsError(_) => 22,
+            Error::StuckFrame(_) => 23,
+            Error::Overexposed(_) => 24,
+            Error::RoiMismatch(_) => 25,
+            Error::WithHint(err, _) => err.code(),
+        }
+    }
+
+    /// Create an error from its numeric code (see [`Error::code`]).
+    ///
+    /// # Arguments
+    /// - `code` - The error code.
+    /// - `detail` - The payload of the error. Numeric payloads are parsed from the
+    ///   string; if the string is not a valid number, a `Message` with the raw
+    ///   string is returned instead. Ignored for errors without a payload.
+    ///
+    /// # Returns
+    /// The error, or `None` if the code is unknown.
+    pub fn from_code(code: u32, detail: &str) -> Option<Self> {
+        let detail_str = || detail.to_string();
+        let err = match code {
+            1 => Error::Message(detail_str()),
+            2 => parse_detail(detail, Error::InvalidIndex),
+            3 => parse_detail(detail, Error::InvalidId),
+            4 => Error::InvalidControlType(detail_str()),
+            5 => Error::NoCamerasAvailable,
+            6 => Error::CameraClosed,
+            7 => Error::CameraRemoved,
+            8 => Error::InvalidPath(detail_str()),
+            9 => Error::InvalidFormat(detail_str()),
+            10 => parse_detail(detail, Error::InvalidSize),
+            11 => Error::InvalidImageType(detail_str()),
+            12 => Error::TimedOut,
+            13 => Error::InvalidSequence,
+            14 => parse_detail(detail, Error::BufferTooSmall),
+            15 => Error::ExposureInProgress,
+            16 => Error::GeneralError(detail_str()),
+            17 => Error::InvalidMode(detail_str()),
+            18 => Error::ExposureFailed(detail_str()),
+            19 => Error::InvalidValue(detail_str()),
+            20 => Error::OutOfBounds(detail_str()),
+            21 => Error::ExposureNotStarted,
+            22 => Error::FitsError(detail_str()),
+            23 => parse_detail(detail, Error::StuckFrame),
+            24 => parse_detail(detail, Error::Overexposed),
+            25 => Error::RoiMismatch(detail_str()),
+            _ => return None,
+        };
+        Some(err)
+    }
+
+    /// Get the suggested action to recover from the error.
+    ///
+    /// Returns the hint attached by the driver using [`Error::with_hint`] if present,
+    /// or a default hint based on the kind of error otherwise.
+    pub fn recovery_hint(&self) -> RecoveryHint {
+        match self {
+            Error::WithHint(_, hint) => *hint,
+            Error::TimedOut | Error::ExposureFailed(_) | Error::ExposureInProgress => {
+                RecoveryHint::Retry
+            }
+            Error::CameraClosed | Error::CameraRemoved | Error::StuckFrame(_) => {
+                RecoveryHint::Reconnect
+            }
+            _ => RecoveryHint::None,
+        }
+    }
+}
+
+/// Parse the numeric payload of an error, falling back to a `Message` with the
+/// raw payload if it is not a valid number.
+fn parse_detail<T: std::str::FromStr>(detail: &str, err: fn(T) -> Error) -> Error {
+    match detail.parse() {
+        Ok(value) => err(value),
+        Err(_) => Error::Message(detail.to_string()),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Suggested action to recover from an [`Error`].
+pub enum RecoveryHint {
+    /// Retry the operation.
+    Retry,
+    /// Reconnect to the camera.
+    Reconnect,
+    /// Power cycle the camera.
+    PowerCycle,
+    /// Reduce the USB bandwidth used by the camera, and retry.
+    ReduceUsbTraffic,
+    /// No automated recovery is possible.
+    None,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_roi() {
+        let roi = parse_roi_str("10, 20,300,400").unwrap();
+        assert_eq!(
+            (roi.x_min, roi.y_min, roi.width, roi.height),
+            (10, 20, 300, 400)
+        );
+        assert_eq!((roi.bin_x, roi.bin_y, roi.skip_x, roi.skip_y), (1, 1, 1, 1));
+        let roi: ROI = "10,20,300,400,2,4".parse().unwrap();
+        assert_eq!((roi.bin_x, roi.bin_y, roi.skip_x, roi.skip_y), (2, 4, 1, 1));
+        let roi = parse_roi_str("10,20,300,400,2,4,3,5").unwrap();
+        assert_eq!((roi.bin_x, roi.bin_y, roi.skip_x, roi.skip_y), (2, 4, 3, 5));
+        let rect = roi.sensor_rect();
+        assert_eq!(
+            (rect.x, rect.y, rect.width, rect.height),
+            (20, 80, 1800, 8000)
+        );
+    }
+
+    #[test]
+    fn parse_roi_rejects_invalid_input() {
+        for s in [
+            "",
+            "1,2,3",
+            "1,2,3,4,5",
+            "1,2,3,4,5,6,7",
+            "1,2,3,4,5,6,7,8,9",
+            "1,2,3,-4",
+            "1,2,3,x",
+            "1,,3,4",
+            "1,2,3,4294967296",
+        ] {
+            assert!(
+                matches!(parse_roi_str(s), Err(Error::InvalidFormat(_))),
+                "{:?}",
+                s
+            );
+        }
+        for s in [
+            "1,2,3,4,0,1",
+            "1,2,3,4,1,1,1,0",
+            "4294967295,0,1,1",
+            "0,0,65536,1,65536,1",
+            "0,0,1,1,65536,1,65536,1",
+            "2147483648,0,1,1,2,1",
+        ] {
+            assert!(
+                matches!(parse_roi_str(s), Err(Error::InvalidValue(_))),
+                "{:?}",
+                s
+            );
+        }
+        assert!(parse_roi_str("4294967294,0,1,1").is_ok());
+    }
+
+    #[test]
+    fn sensor_rect_saturates() {
+        let roi = ROI {
+            x_min: u32::MAX,
+            y_min: 0,
+            width: u32::MAX,
+            height: 1,
+            bin_x: 2,
+            bin_y: 1,
+            skip_x: 1,
+            skip_y: 1,
+        };
+        assert_eq!(roi.checked_sensor_rect(), None);
+        let rect = roi.sensor_rect();
+        assert_eq!((rect.x, rect.width), (u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn error_code_round_trip() {
+        let errors = [
+            (Error::Message("message".to_string()), "message"),
+            (Error::InvalidIndex(-1), "-1"),
+            (Error::InvalidId(7), "7"),
+            (Error::InvalidControlType("gain".to_string()), "gain"),
+            (Error::NoCamerasAvailable, ""),
+            (Error::CameraClosed, ""),
+            (Error::CameraRemoved, ""),
+            (Error::InvalidPath("/tmp".to_string()), "/tmp"),
+            (Error::InvalidFormat("format".to_string()), "format"),
+            (Error::InvalidSize(1024), "1024"),
+            (Error::InvalidImageType("rgb".to_string()), "rgb"),
+            (Error::TimedOut, ""),
+            (Error::InvalidSequence, ""),
+            (Error::BufferTooSmall(16), "16"),
+            (Error::ExposureInProgress, ""),
+            (Error::GeneralError("general".to_string()), "general"),
+            (Error::InvalidMode("mode".to_string()), "mode"),
+            (Error::ExposureFailed("failed".to_string()), "failed"),
+            (Error::InvalidValue("value".to_string()), "value"),
+            (Error::OutOfBounds("bounds".to_string()), "bounds"),
+            (Error::ExposureNotStarted, ""),
+            (Error::FitsError("fits".to_string()), "fits"),
+            (Error::StuckFrame(3), "3"),
+            (Error::Overexposed(0.5), "0.5"),
+            (Error::RoiMismatch("roi".to_string()), "roi"),
+        ];
+        for (code, (err, detail)) in errors.iter().enumerate() {
+            assert_eq!(err.code(), code as u32 + 1);
+            assert_eq!(Error::from_code(err.code(), detail).as_ref(), Some(err));
+        }
+        let hinted = Error::TimedOut.with_hint(RecoveryHint::PowerCycle);
+        assert_eq!(hinted.code(), Error::TimedOut.code());
+        assert_eq!(hinted.root(), &Error::TimedOut);
+        assert_eq!(Error::from_code(0, ""), None);
+        assert_eq!(Error::from_code(26, ""), None);
+    }
+
+    #[test]
+    fn error_from_code_invalid_payload() {
+        assert_eq!(
+            Error::from_code(10, "large"),
+            Some(Error::Message("large".to_string()))
+        );
+        assert_eq!(
+            Error::from_code(2, ""),
+            Some(Error::Message("".to_string()))
+        );
+    }
 }
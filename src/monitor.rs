@@ -0,0 +1,327 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{luma_pixels, mean, percentile};
+use crate::{CameraUnit, DynamicSerialImage, Error};
+
+/// Detects a driver repeatedly returning the same frame buffer, a common
+/// failure mode with unreliable USB connections.
+///
+/// Every frame passed to [`StuckFrameDetector::check`] is hashed and compared
+/// with the previous frame.
+#[derive(Clone, Debug)]
+pub struct StuckFrameDetector {
+    max_repeats: usize,
+    last: Option<u64>,
+    repeats: usize,
+}
+
+impl StuckFrameDetector {
+    /// Create a new stuck frame detector.
+    ///
+    /// # Arguments
+    /// - `max_repeats` - The number of consecutive identical frames tolerated
+    ///   before an error is raised. A value of `0` is treated as `1`.
+    pub fn new(max_repeats: usize) -> Self {
+        Self {
+            max_repeats: max_repeats.max(1),
+            last: None,
+            repeats: 0,
+        }
+    }
+
+    /// Check a frame against the previous frame.
+    ///
+    /// # Errors
+    /// [`Error::StuckFrame`] with the number of consecutive repeats if the frame
+    /// is identical to the previous `max_repeats` frames.
+    pub fn check(&mut self, img: &DynamicSerialImage) -> Result<(), Error> {
+        self.check_pixels(img.width(), img.height(), &luma_pixels(img))
+    }
+
+    fn check_pixels(&mut self, width: usize, height: usize, pixels: &[u16]) -> Result<(), Error> {
+        let mut hasher = DefaultHasher::new();
+        width.hash(&mut hasher);
+        height.hash(&mut hasher);
+        pixels.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.last == Some(hash) {
+            self.repeats += 1;
+        } else {
+            self.last = Some(hash);
+            self.repeats = 0;
+        }
+        if self.repeats >= self.max_repeats {
+            Err(Error::StuckFrame(self.repeats))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get the number of consecutive repeats of the last frame.
+    pub fn repeats(&self) -> usize {
+        self.repeats
+    }
+
+    /// Reset the detector, e.g. after reconnecting to the camera.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.repeats = 0;
+    }
+}
+
+/// Aborts acquisition when frames are overexposed for several frames in a row,
+/// protecting the sensor during solar imaging mishaps.
+#[derive(Clone, Debug)]
+pub struct OverexposureGuard {
+    threshold: f64,
+    full_scale: f64,
+    max_frames: usize,
+    count: usize,
+}
+
+impl OverexposureGuard {
+    /// Create a new overexposure guard.
+    ///
+    /// # Arguments
+    /// - `threshold` - The mean frame level, as a fraction of full scale in `[0, 1]`,
+    ///   above which a frame is considered overexposed.
+    /// - `max_frames` - The number of consecutive overexposed frames that trip the
+    ///   guard. A value of `0` is treated as `1`.
+    pub fn new(threshold: f64, max_frames: usize) -> Self {
+        Self {
+            threshold: threshold.clamp(0.0, 1.0),
+            full_scale: u16::MAX as f64,
+            max_frames: max_frames.max(1),
+            count: 0,
+        }
+    }
+
+    /// Set the full-scale pixel level. Defaults to `u16::MAX`.
+    ///
+    /// Set this to the saturation level of cameras that do not scale their data to
+    /// 16 bits, e.g. [`crate::PixelBpp::full_scale`] of the camera bit depth.
+    pub fn set_full_scale(&mut self, full_scale: f64) {
+        self.full_scale = full_scale.max(1.0);
+    }
+
+    /// Check a frame.
+    ///
+    /// # Errors
+    /// [`Error::Overexposed`] with the mean frame level (as a fraction of full scale)
+    /// if the guard has tripped.
+    pub fn check(&mut self, img: &DynamicSerialImage) -> Result<(), Error> {
+        self.check_level(mean(&luma_pixels(img)) / self.full_scale)
+    }
+
+    fn check_level(&mut self, level: f64) -> Result<(), Error> {
+        if level > self.threshold {
+            self.count += 1;
+        } else {
+            self.count = 0;
+        }
+        if self.count >= self.max_frames {
+            log::warn!(
+                "Overexposure: mean level {:.3} for {} frames",
+                level,
+                self.count
+            );
+            Err(Error::Overexposed(level))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check a frame, and cancel any ongoing capture on the camera if the guard has tripped.
+    ///
+    /// # Errors
+    /// [`Error::Overexposed`] if the guard has tripped, or any error raised while
+    /// cancelling the capture.
+    pub fn check_and_cancel(
+        &mut self,
+        img: &DynamicSerialImage,
+        cam: &dyn CameraUnit,
+    ) -> Result<(), Error> {
+        self.check(img).map_err(|e| match cam.cancel_capture() {
+            Ok(_) => e,
+            Err(err) => err,
+        })
+    }
+
+    /// Reset the guard.
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// A transient detected by a [`TransientDetector`].
+pub struct TransientEvent {
+    /// The number of pixels that brightened above the threshold.
+    pub pixels: usize,
+    /// The largest brightening of a pixel (in ADU).
+    pub peak: f64,
+    /// The brightness-weighted centroid `(x, y)` of the brightened pixels.
+    pub centroid: (f64, f64),
+}
+
+/// Detects bright transients (e.g. meteors) in a stream of frames by differencing
+/// consecutive frames.
+///
+/// A pixel has brightened if its increase from the previous frame exceeds a
+/// multiple of the noise of the frame difference. A transient is detected when
+/// enough pixels have brightened.
+#[derive(Clone, Debug)]
+pub struct TransientDetector {
+    sigma: f64,
+    min_pixels: usize,
+    last: Option<(usize, Vec<u16>)>,
+}
+
+impl TransientDetector {
+    /// Create a new transient detector.
+    ///
+    /// # Arguments
+    /// - `sigma` - The brightening threshold, as a multiple of the noise of the frame
+    ///   difference. A value that is not finite and positive is treated as `1.0`.
+    /// - `min_pixels` - The minimum number of brightened pixels for a detection. A
+    ///   value of `0` is treated as `1`.
+    pub fn new(sigma: f64, min_pixels: usize) -> Self {
+        Self {
+            sigma: if sigma.is_finite() && sigma > 0.0 {
+                sigma
+            } else {
+                1.0
+            },
+            min_pixels: min_pixels.max(1),
+            last: None,
+        }
+    }
+
+    /// Check a frame against the previous frame.
+    ///
+    /// # Returns
+    /// The transient, if one was detected. No transient is detected on the first
+    /// frame, or when the frame size changes.
+    pub fn check(&mut self, img: &DynamicSerialImage) -> Option<TransientEvent> {
+        self.check_pixels(img.width(), luma_pixels(img))
+    }
+
+    fn check_pixels(&mut self, width: usize, pixels: Vec<u16>) -> Option<TransientEvent> {
+        let last = self.last.replace((width, pixels));
+        let (last_width, last_pixels) = last?;
+        let (_, pixels) = self.last.as_ref()?;
+        if last_width != width || last_pixels.len() != pixels.len() {
+            return None;
+        }
+        let diff: Vec<f64> = pixels
+            .iter()
+            .zip(last_pixels.iter())
+            .map(|(&a, &b)| a as f64 - b as f64)
+            .collect();
+        let abs_diff: Vec<u16> = diff
+            .iter()
+            .map(|d| d.abs().min(u16::MAX as f64) as u16)
+            .collect();
+        let threshold = self.sigma * (1.4826 * percentile(&abs_diff, 0.5)).max(1.0);
+        let (mut count, mut peak, mut sum, mut sum_x, mut sum_y) = (0, 0.0f64, 0.0, 0.0, 0.0);
+        for (idx, &d) in diff.iter().enumerate() {
+            if d > threshold {
+                count += 1;
+                peak = peak.max(d);
+                sum += d;
+                sum_x += d * (idx % width) as f64;
+                sum_y += d * (idx / width) as f64;
+            }
+        }
+        if count < self.min_pixels {
+            return None;
+        }
+        log::info!("Transient: {} pixels, peak {:.0} ADU", count, peak);
+        Some(TransientEvent {
+            pixels: count,
+            peak,
+            centroid: (sum_x / sum, sum_y / sum),
+        })
+    }
+
+    /// Reset the detector.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_frame_detector() {
+        let mut detector = StuckFrameDetector::new(2);
+        let (frame, other) = (vec![1u16, 2, 3, 4], vec![4u16, 3, 2, 1]);
+        assert!(detector.check_pixels(2, 2, &frame).is_ok());
+        assert!(detector.check_pixels(2, 2, &frame).is_ok());
+        assert_eq!(
+            detector.check_pixels(2, 2, &frame),
+            Err(Error::StuckFrame(2))
+        );
+        assert!(detector.check_pixels(2, 2, &other).is_ok());
+        assert_eq!(detector.repeats(), 0);
+        // Same pixels with a different shape are a different frame.
+        assert!(detector.check_pixels(4, 1, &other).is_ok());
+        assert!(detector.check_pixels(4, 1, &other).is_ok());
+        detector.reset();
+        assert!(detector.check_pixels(4, 1, &other).is_ok());
+        assert_eq!(detector.repeats(), 0);
+    }
+
+    #[test]
+    fn overexposure_guard() {
+        let mut guard = OverexposureGuard::new(0.9, 2);
+        assert!(guard.check_level(0.95).is_ok());
+        assert!(guard.check_level(0.5).is_ok());
+        assert!(guard.check_level(0.95).is_ok());
+        assert_eq!(guard.check_level(0.99), Err(Error::Overexposed(0.99)));
+        assert_eq!(guard.check_level(0.99), Err(Error::Overexposed(0.99)));
+        guard.reset();
+        assert!(guard.check_level(0.99).is_ok());
+    }
+
+    #[test]
+    fn transient_detector() {
+        let mut detector = TransientDetector::new(5.0, 4);
+        let frame = vec![100u16; 100];
+        assert_eq!(detector.check_pixels(10, frame.clone()), None);
+        assert_eq!(detector.check_pixels(10, frame.clone()), None);
+
+        let mut blob = frame.clone();
+        for idx in [44, 45, 54, 55] {
+            blob[idx] = 200;
+        }
+        let event = detector.check_pixels(10, blob.clone()).unwrap();
+        assert_eq!(event.pixels, 4);
+        assert_eq!(event.peak, 100.0);
+        assert_eq!(event.centroid, (4.5, 4.5));
+        // Fading back is not a brightening.
+        assert_eq!(detector.check_pixels(10, frame.clone()), None);
+
+        assert_eq!(detector.check_pixels(20, blob.clone()), None);
+        detector.reset();
+        assert_eq!(detector.check_pixels(10, blob), None);
+    }
+
+    #[test]
+    fn transient_detector_invalid_sigma() {
+        for sigma in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let mut detector = TransientDetector::new(sigma, 1);
+            let frame = vec![100u16; 16];
+            assert_eq!(detector.check_pixels(4, frame.clone()), None);
+            assert_eq!(detector.check_pixels(4, frame.clone()), None);
+            let mut bright = frame;
+            bright[5] = 200;
+            assert!(detector.check_pixels(4, bright).is_some());
+        }
+    }
+}
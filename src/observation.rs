@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{DynamicSerialImage, ImageMetaData};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// Information about an observation, written into the FITS header of every frame.
+pub struct ObservationInfo {
+    /// The name of the target (`OBJECT`).
+    pub object: Option<String>,
+    /// The name of the observer (`OBSERVER`).
+    pub observer: Option<String>,
+    /// The name of the telescope (`TELESCOP`).
+    pub telescope: Option<String>,
+}
+
+impl ObservationInfo {
+    /// Add the target (`OBJECT`), observer (`OBSERVER`) and telescope (`TELESCOP`)
+    /// to the image metadata, to be written into the FITS header. Fields that are
+    /// `None` are skipped.
+    pub fn update_metadata(&self, meta: &mut ImageMetaData) {
+        let keys = [
+            ("OBJECT", &self.object),
+            ("OBSERVER", &self.observer),
+            ("TELESCOP", &self.telescope),
+        ];
+        for (key, val) in keys {
+            if let Some(val) = val {
+                meta.add_extended_attrib(key, val);
+            }
+        }
+    }
+
+    /// Add the observation information to the metadata of an image
+    /// (see [`ObservationInfo::update_metadata`]).
+    pub fn stamp(&self, img: &mut DynamicSerialImage) {
+        if let Some(mut meta) = img.get_metadata() {
+            self.update_metadata(&mut meta);
+            img.set_metadata(meta);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CameraUnit, ImageMetaData, ROI};
+
+/// Arcseconds per radian, scaled for pixel sizes in microns and focal lengths in millimeters.
+const ARCSEC_PER_UM_PER_MM: f64 = 206.264_806;
+
+/// Get the pixel scale (in arcseconds per pixel).
+///
+/// # Arguments
+/// - `pixel_size` - The (un-binned) pixel size in microns.
+/// - `bin` - The binning factor.
+/// - `focal_length` - The focal length in millimeters.
+pub fn pixel_scale(pixel_size: f32, bin: u32, focal_length: f64) -> f64 {
+    ARCSEC_PER_UM_PER_MM * pixel_size as f64 * bin.max(1) as f64 / focal_length
+}
+
+/// Get the field of view (in arcminutes) along one axis.
+///
+/// # Arguments
+/// - `pixel_size` - The (un-binned) pixel size in microns.
+/// - `pixels` - The number of (binned) pixels along the axis.
+/// - `bin` - The binning factor.
+/// - `focal_length` - The focal length in millimeters.
+pub fn field_of_view(pixel_size: f32, pixels: u32, bin: u32, focal_length: f64) -> f64 {
+    pixel_scale(pixel_size, bin, focal_length) * pixels as f64 / 60.0
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Pixel scale and field of view of a camera behind an optical system.
+pub struct PlateScale {
+    /// The focal length in millimeters.
+    pub focal_length: f64,
+    /// The pixel scale (X, Y) in arcseconds per pixel.
+    pub scale: (f64, f64),
+    /// The field of view (X, Y) in arcminutes.
+    pub fov: (f64, f64),
+}
+
+impl PlateScale {
+    /// Calculate the plate scale for a region of interest.
+    ///
+    /// # Arguments
+    /// - `pixel_size` - The (un-binned) pixel size (X, Y) in microns.
+    /// - `roi` - The region of interest.
+    /// - `focal_length` - The focal length in millimeters.
+    pub fn new(pixel_size: (f32, f32), roi: &ROI, focal_length: f64) -> Self {
+        Self {
+            focal_length,
+            scale: (
+                pixel_scale(pixel_size.0, roi.bin_x, focal_length),
+                pixel_scale(pixel_size.1, roi.bin_y, focal_length),
+            ),
+            fov: (
+                field_of_view(pixel_size.0, roi.width, roi.bin_x, focal_length),
+                field_of_view(pixel_size.1, roi.height, roi.bin_y, focal_length),
+            ),
+        }
+    }
+
+    /// Calculate the plate scale for the current region of interest of a camera.
+    ///
+    /// # Returns
+    /// `None` if the camera does not report its pixel size.
+    pub fn from_camera(cam: &dyn CameraUnit, focal_length: f64) -> Option<Self> {
+        cam.get_pixel_size()
+            .map(|size| Self::new(size, cam.get_roi(), focal_length))
+    }
+
+    /// Add the focal length (`FOCALLEN`, in mm) and pixel scale (in arcseconds per
+    /// pixel) to the image metadata, to be written into the FITS header.
+    ///
+    /// The pixel scale is written as `SCALE` if both axes have the same scale (at
+    /// the written precision), and as `SCALEX` and `SCALEY` otherwise, e.g. for
+    /// non-square pixels or unequal binning.
+    pub fn update_metadata(&self, meta: &mut ImageMetaData) {
+        for (key, value) in self.keys() {
+            meta.add_extended_attrib(key, &value);
+        }
+    }
+
+    fn keys(&self) -> Vec<(&'static str, String)> {
+        let mut keys = vec![("FOCALLEN", format!("{:.2}", self.focal_length))];
+        let (x, y) = (
+            format!("{:.4}", self.scale.0),
+            format!("{:.4}", self.scale.1),
+        );
+        if x == y {
+            keys.push(("SCALE", x));
+        } else {
+            keys.push(("SCALEX", x));
+            keys.push(("SCALEY", y));
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roi(bin_x: u32, bin_y: u32) -> ROI {
+        ROI {
+            x_min: 0,
+            y_min: 0,
+            width: 1000,
+            height: 500,
+            bin_x,
+            bin_y,
+            skip_x: 1,
+            skip_y: 1,
+        }
+    }
+
+    #[test]
+    fn pixel_scale_known_values() {
+        assert!((pixel_scale(3.76, 1, 1000.0) - 0.775_556).abs() < 1e-6);
+        assert!((pixel_scale(3.76, 2, 1000.0) - 1.551_112).abs() < 1e-6);
+        assert!((field_of_view(3.76, 1000, 1, 1000.0) - 12.925_93).abs() < 1e-4);
+    }
+
+    #[test]
+    fn plate_scale_keys() {
+        let plate = PlateScale::new((3.76, 3.76), &roi(2, 2), 1000.0);
+        assert!((plate.fov.1 - 12.925_93).abs() < 1e-4);
+        assert_eq!(
+            plate.keys(),
+            vec![
+                ("FOCALLEN", "1000.00".to_string()),
+                ("SCALE", "1.5511".to_string())
+            ]
+        );
+        let plate = PlateScale::new((3.76, 3.76), &roi(1, 2), 1000.0);
+        assert_eq!(
+            plate.keys()[1..],
+            [
+                ("SCALEX", "0.7756".to_string()),
+                ("SCALEY", "1.5511".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{luma_pixels, percentile, robust_sigma};
+use crate::{DynamicSerialImage, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Result of an aperture photometry measurement.
+pub struct FluxMeasurement {
+    /// The background-subtracted flux in the aperture (in ADU).
+    pub flux: f64,
+    /// The estimated uncertainty (one standard deviation) of the flux (in ADU).
+    pub error: f64,
+    /// The background level (in ADU per pixel), estimated from the annulus.
+    pub background: f64,
+    /// The number of pixels in the aperture.
+    pub aperture_pixels: usize,
+    /// The number of pixels in the annulus.
+    pub annulus_pixels: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Aperture photometry on calibrated (bias, dark and flat corrected) frames.
+///
+/// The flux is summed over the pixels whose centers lie within a circular aperture,
+/// and the background is estimated as the median of the pixels in a surrounding
+/// annulus. Pixel coordinates are in (binned) pixels of the frame, with the origin
+/// at the center of the top left pixel.
+pub struct AperturePhotometry {
+    /// The detector gain (in electrons per ADU), used to estimate the photon noise.
+    pub gain: f64,
+}
+
+impl Default for AperturePhotometry {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+impl AperturePhotometry {
+    /// Measure the background-subtracted flux of an object.
+    ///
+    /// # Arguments
+    /// - `img` - The calibrated frame.
+    /// - `position` - The position `(x, y)` of the object, e.g. its centroid.
+    /// - `r_aperture` - The radius of the aperture.
+    /// - `r_annulus` - The inner and outer radii of the background annulus.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidValue`] if the aperture radius is not positive, or the annulus
+    ///   does not lie outside the aperture.
+    /// - [`Error::OutOfBounds`] if the aperture or the annulus contains no pixels of
+    ///   the frame.
+    pub fn measure_flux(
+        &self,
+        img: &DynamicSerialImage,
+        position: (f64, f64),
+        r_aperture: f64,
+        r_annulus: (f64, f64),
+    ) -> Result<FluxMeasurement, Error> {
+        self.measure(
+            &luma_pixels(img),
+            img.width(),
+            position,
+            r_aperture,
+            r_annulus,
+        )
+    }
+
+    fn measure(
+        &self,
+        pixels: &[u16],
+        width: usize,
+        position: (f64, f64),
+        r_aperture: f64,
+        r_annulus: (f64, f64),
+    ) -> Result<FluxMeasurement, Error> {
+        let (r_inner, r_outer) = r_annulus;
+        if r_aperture.is_nan() || r_aperture <= 0.0 {
+            return Err(Error::InvalidValue(format!(
+                "Aperture radius {} must be positive",
+                r_aperture
+            )));
+        }
+        if r_inner.is_nan() || r_outer.is_nan() || r_inner < r_aperture || r_outer <= r_inner {
+            return Err(Error::InvalidValue(format!(
+                "Annulus ({}, {}) must lie outside the aperture radius {}",
+                r_inner, r_outer, r_aperture
+            )));
+        }
+        let (mut aperture, mut annulus) = (Vec::new(), Vec::new());
+        if let Some(height) = pixels.len().checked_div(width) {
+            let (x, y) = position;
+            let x_range = (x - r_outer).floor().max(0.0) as usize
+                ..((x + r_outer).ceil().max(-1.0) + 1.0).min(width as f64) as usize;
+            let y_range = (y - r_outer).floor().max(0.0) as usize
+                ..((y + r_outer).ceil().max(-1.0) + 1.0).min(height as f64) as usize;
+            for row in y_range {
+                for col in x_range.clone() {
+                    let r = (col as f64 - x).hypot(row as f64 - y);
+                    let p = pixels[row * width + col];
+                    if r <= r_aperture {
+                        aperture.push(p);
+                    } else if r >= r_inner && r <= r_outer {
+                        annulus.push(p);
+                    }
+                }
+            }
+        }
+        if aperture.is_empty() || annulus.is_empty() {
+            return Err(Error::OutOfBounds(format!(
+                "Aperture at ({:.1}, {:.1}) is outside the frame",
+                position.0, position.1
+            )));
+        }
+        let background = percentile(&annulus, 0.5);
+        let sigma = robust_sigma(&annulus);
+        let (n_ap, n_ann) = (aperture.len() as f64, annulus.len() as f64);
+        let flux = aperture.iter().map(|&p| p as f64).sum::<f64>() - n_ap * background;
+        // Photon noise of the object, background noise in the aperture, and the
+        // uncertainty of the background estimate.
+        let variance = flux.max(0.0) / self.gain.max(f64::EPSILON)
+            + n_ap * sigma * sigma * (1.0 + n_ap / n_ann);
+        Ok(FluxMeasurement {
+            flux,
+            error: variance.sqrt(),
+            background,
+            aperture_pixels: aperture.len(),
+            annulus_pixels: annulus.len(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// A differential photometry measurement of a single frame.
+pub struct LightCurvePoint {
+    /// The time of the frame.
+    pub time: SystemTime,
+    /// The target measurement.
+    pub target: FluxMeasurement,
+    /// The summed flux (in ADU) of the comparison stars.
+    pub comparison_flux: f64,
+    /// The uncertainty (one standard deviation) of the summed comparison flux (in ADU).
+    pub comparison_error: f64,
+    /// The magnitude of the target relative to the comparison ensemble.
+    pub magnitude: f64,
+    /// The uncertainty of the relative magnitude.
+    pub magnitude_error: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Accumulates an ensemble differential light curve of a target.
+///
+/// Every frame added to the light curve is measured with [`AperturePhotometry`]
+/// at the target and comparison star positions. The comparison fluxes are summed
+/// into an ensemble, and the target magnitude is recorded relative to the ensemble,
+/// which cancels transparency and airmass changes common to all stars.
+pub struct LightCurve {
+    photometry: AperturePhotometry,
+    target: (f64, f64),
+    comparisons: Vec<(f64, f64)>,
+    r_aperture: f64,
+    r_annulus: (f64, f64),
+    points: Vec<LightCurvePoint>,
+}
+
+impl LightCurve {
+    /// Create a new, empty light curve.
+    ///
+    /// # Arguments
+    /// - `photometry` - The photometry settings.
+    /// - `target` - The position `(x, y)` of the target.
+    /// - `comparisons` - The positions of the comparison stars.
+    /// - `r_aperture` - The radius of the aperture.
+    /// - `r_annulus` - The inner and outer radii of the background annulus.
+    pub fn new(
+        photometry: AperturePhotometry,
+        target: (f64, f64),
+        comparisons: &[(f64, f64)],
+        r_aperture: f64,
+        r_annulus: (f64, f64),
+    ) -> Self {
+        Self {
+            photometry,
+            target,
+            comparisons: comparisons.to_vec(),
+            r_aperture,
+            r_annulus,
+            points: Vec::new(),
+        }
+    }
+
+    /// Get the measurements, in the order the frames were added.
+    pub fn points(&self) -> &[LightCurvePoint] {
+        &self.points
+    }
+
+    /// Measure a calibrated frame and append the measurement to the light curve.
+    /// The time of the frame is the timestamp of the image metadata.
+    ///
+    /// # Returns
+    /// The new measurement, or error. Nothing is appended on error.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidFormat`] if the image has no metadata.
+    /// - [`Error::InvalidValue`] if there are no comparison stars, or the target or
+    ///   the ensemble flux is not positive.
+    /// - Errors of [`AperturePhotometry::measure_flux`].
+    pub fn add_frame(&mut self, img: &DynamicSerialImage) -> Result<&LightCurvePoint, Error> {
+        let time = img
+            .get_metadata()
+            .ok_or(Error::InvalidFormat("Image has no metadata".to_string()))?
+            .timestamp;
+        self.add(time, &luma_pixels(img), img.width())
+    }
+
+    fn add(
+        &mut self,
+        time: SystemTime,
+        pixels: &[u16],
+        width: usize,
+    ) -> Result<&LightCurvePoint, Error> {
+        if self.comparisons.is_empty() {
+            return Err(Error::InvalidValue("No comparison stars".to_string()));
+        }
+        let measure = |position| {
+            self.photometry
+                .measure(pixels, width, position, self.r_aperture, self.r_annulus)
+        };
+        let target = measure(self.target)?;
+        let (mut comparison_flux, mut comparison_variance) = (0.0, 0.0);
+        for &position in &self.comparisons {
+            let res = measure(position)?;
+            comparison_flux += res.flux;
+            comparison_variance += res.error * res.error;
+        }
+        if target.flux <= 0.0 || comparison_flux <= 0.0 {
+            return Err(Error::InvalidValue(format!(
+                "Target flux {} and ensemble flux {} must be positive",
+                target.flux, comparison_flux
+            )));
+        }
+        let comparison_error = comparison_variance.sqrt();
+        let magnitude_error = 2.5
+            * std::f64::consts::LOG10_E
+            * ((target.error / target.flux).powi(2) + (comparison_error / comparison_flux).powi(2))
+                .sqrt();
+        self.points.push(LightCurvePoint {
+            time,
+            target,
+            comparison_flux,
+            comparison_error,
+            magnitude: -2.5 * (target.flux / comparison_flux).log10(),
+            magnitude_error,
+        });
+        Ok(self.points.last().unwrap())
+    }
+
+    /// Format the light curve as CSV, with one row per frame. The time is given in
+    /// seconds since the Unix epoch.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "time,target_flux,target_error,comparison_flux,comparison_error,magnitude,magnitude_error\n",
+        );
+        for point in &self.points {
+            let time = point
+                .time
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64();
+            let _ = writeln!(
+                csv,
+                "{:.6},{},{},{},{},{:.6},{:.6}",
+                time,
+                point.target.flux,
+                point.target.error,
+                point.comparison_flux,
+                point.comparison_error,
+                point.magnitude,
+                point.magnitude_error
+            );
+        }
+        csv
+    }
+
+    /// Save the light curve as CSV (see [`LightCurve::to_csv`]).
+    ///
+    /// # Errors
+    /// [`Error::InvalidPath`] if the file can not be written.
+    pub fn save_csv(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.to_csv())
+            .map_err(|e| Error::InvalidPath(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_flux_subtracts_background() {
+        let width = 21;
+        let mut pixels = vec![100u16; width * width];
+        for (dx, dy, p) in [
+            (0, 0, 500),
+            (1, 0, 200),
+            (0, 1, 200),
+            (-1, 0, 200),
+            (0, -1, 200),
+        ] {
+            pixels[(10 + dy) as usize * width + (10 + dx) as usize] = 100 + p;
+        }
+        let photometry = AperturePhotometry::default();
+        let res = photometry
+            .measure(&pixels, width, (10.0, 10.0), 3.0, (5.0, 8.0))
+            .unwrap();
+        assert_eq!(res.background, 100.0);
+        assert_eq!(res.flux, 1300.0);
+        assert_eq!(res.error, 1300f64.sqrt());
+        assert_eq!(res.aperture_pixels, 29);
+    }
+
+    #[test]
+    fn light_curve_differential_magnitude() {
+        let width = 30;
+        let mut pixels = vec![100u16; width * width];
+        pixels[7 * width + 7] = 1100;
+        pixels[7 * width + 22] = 600;
+        pixels[22 * width + 22] = 600;
+        let mut curve = LightCurve::new(
+            AperturePhotometry::default(),
+            (7.0, 7.0),
+            &[(22.0, 7.0), (22.0, 22.0)],
+            2.0,
+            (3.0, 5.0),
+        );
+        let point = *curve.add(UNIX_EPOCH, &pixels, width).unwrap();
+        assert_eq!(point.target.flux, 1000.0);
+        assert_eq!(point.comparison_flux, 1000.0);
+        assert_eq!(point.magnitude, 0.0);
+
+        pixels[7 * width + 7] = 200;
+        let point = *curve.add(UNIX_EPOCH, &pixels, width).unwrap();
+        assert!((point.magnitude - 2.5).abs() < 1e-9);
+
+        pixels[7 * width + 7] = 100;
+        assert!(matches!(
+            curve.add(UNIX_EPOCH, &pixels, width),
+            Err(Error::InvalidValue(_))
+        ));
+        assert_eq!(curve.points().len(), 2);
+        let csv = curve.to_csv();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.lines().nth(2).unwrap().starts_with("0.000000,100,"));
+    }
+
+    #[test]
+    fn measure_flux_rejects_invalid_apertures() {
+        let pixels = vec![0u16; 100];
+        let photometry = AperturePhotometry::default();
+        let measure = |position, r_aperture, r_annulus| {
+            photometry.measure(&pixels, 10, position, r_aperture, r_annulus)
+        };
+        assert!(matches!(
+            measure((5.0, 5.0), 0.0, (1.0, 2.0)),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            measure((5.0, 5.0), 2.0, (1.0, 3.0)),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            measure((50.0, 5.0), 1.0, (2.0, 3.0)),
+            Err(Error::OutOfBounds(_))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::forward::forward_camera_unit;
+use crate::{AnyCameraUnit, CameraUnit, DynamicSerialImage, Error, FrameCallback, RoiImage, ROI};
+
+/// A processing step applied to every frame downloaded through a [`ProcessingCamera`],
+/// e.g. defect removal, flip normalization or pixel format scaling.
+///
+/// Implemented for closures taking and returning a [`DynamicSerialImage`].
+pub trait FrameProcessor: Send {
+    /// Process a frame.
+    fn process(&self, img: DynamicSerialImage) -> Result<DynamicSerialImage, Error>;
+}
+
+impl<F> FrameProcessor for F
+where
+    F: Fn(DynamicSerialImage) -> Result<DynamicSerialImage, Error> + Send,
+{
+    fn process(&self, img: DynamicSerialImage) -> Result<DynamicSerialImage, Error> {
+        self(img)
+    }
+}
+
+/// Wrapper around a camera that applies a chain of [`FrameProcessor`]s to every
+/// frame, regardless of which capture API was used to obtain it.
+///
+/// All other calls are passed through to the wrapped camera.
+pub struct ProcessingCamera {
+    cam: AnyCameraUnit,
+    processors: Arc<Mutex<Vec<Box<dyn FrameProcessor>>>>,
+}
+
+fn lock_chain(
+    processors: &Mutex<Vec<Box<dyn FrameProcessor>>>,
+) -> MutexGuard<'_, Vec<Box<dyn FrameProcessor>>> {
+    processors.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn run_chain(
+    processors: &Mutex<Vec<Box<dyn FrameProcessor>>>,
+    img: DynamicSerialImage,
+) -> Result<DynamicSerialImage, Error> {
+    lock_chain(processors)
+        .iter()
+        .try_fold(img, |img, processor| processor.process(img))
+}
+
+impl ProcessingCamera {
+    /// Wrap a camera, with an empty processing chain.
+    pub fn new(cam: AnyCameraUnit) -> Self {
+        Self {
+            cam,
+            processors: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Append a processor to the chain. Processors are applied in the order they are added.
+    pub fn add_processor(&mut self, processor: Box<dyn FrameProcessor>) -> &mut Self {
+        lock_chain(&self.processors).push(processor);
+        self
+    }
+
+    /// Remove all processors from the chain.
+    pub fn clear_processors(&mut self) {
+        lock_chain(&self.processors).clear();
+    }
+
+    /// Unwrap the camera.
+    pub fn into_inner(self) -> AnyCameraUnit {
+        self.cam
+    }
+
+    fn process(&self, img: DynamicSerialImage) -> Result<DynamicSerialImage, Error> {
+        run_chain(&self.processors, img)
+    }
+}
+
+impl CameraUnit for ProcessingCamera {
+    forward_camera_unit!(cam);
+
+    fn capture_image(&self) -> Result<DynamicSerialImage, Error> {
+        self.process(self.cam.capture_image()?)
+    }
+
+    fn download_image(&self) -> Result<DynamicSerialImage, Error> {
+        self.process(self.cam.download_image()?)
+    }
+
+    fn download_tile(&self, tile: &ROI) -> Result<DynamicSerialImage, Error> {
+        self.process(self.cam.download_tile(tile)?)
+    }
+
+    fn get_partial_readout(&self) -> Result<DynamicSerialImage, Error> {
+        self.process(self.cam.get_partial_readout()?)
+    }
+
+    fn next_frame(&self, timeout: Duration) -> Result<DynamicSerialImage, Error> {
+        self.process(self.cam.next_frame(timeout)?)
+    }
+
+    fn set_frame_callback(&mut self, mut callback: FrameCallback) -> Result<(), Error> {
+        let processors = self.processors.clone();
+        let name = self.cam.camera_name().to_string();
+        self.cam
+            .set_frame_callback(Box::new(move |img| match run_chain(&processors, img) {
+                Ok(img) => callback(img),
+                Err(e) => log::warn!("{}: Dropping frame: {}", name, e),
+            }))
+    }
+
+    fn set_exposure(&mut self, exposure: Duration) -> Result<Duration, Error> {
+        self.cam.set_exposure(exposure)
+    }
+
+    fn set_roi(&mut self, roi: &ROI) -> Result<&ROI, Error> {
+        self.cam.set_roi(roi)
+    }
+
+    fn capture_multi_roi(&self) -> Result<Vec<RoiImage>, Error> {
+        self.cam
+            .capture_multi_roi()?
+            .into_iter()
+            .map(|img| {
+                Ok(RoiImage {
+                    image: self.process(img.image)?,
+                    ..img
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, ROI};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Known workarounds for a camera, declared by the driver through
+/// [`crate::CameraUnit::get_quirks`] and consulted by the helpers in this crate.
+pub struct Quirks {
+    /// Time to wait after changing the gain before the next exposure.
+    pub gain_settle: Duration,
+    /// Time to wait after changing the exposure time before the next exposure.
+    pub exposure_settle: Duration,
+    /// Time to wait after changing the detector temperature set point before the next exposure.
+    pub temperature_settle: Duration,
+    /// The origin of the region of interest must be a multiple of this (x, y).
+    pub roi_origin_multiple: (u32, u32),
+    /// The size of the region of interest must be a multiple of this (width, height).
+    pub roi_size_multiple: (u32, u32),
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self {
+            gain_settle: Duration::ZERO,
+            exposure_settle: Duration::ZERO,
+            temperature_settle: Duration::ZERO,
+            roi_origin_multiple: (1, 1),
+            roi_size_multiple: (1, 1),
+        }
+    }
+}
+
+impl Quirks {
+    /// Get the time to wait before the next exposure after a set of settings changed.
+    ///
+    /// # Arguments
+    /// - `exposure` - The exposure time changed.
+    /// - `gain` - The gain changed.
+    /// - `temperature` - The detector temperature set point changed.
+    pub fn settle_time(&self, exposure: bool, gain: bool, temperature: bool) -> Duration {
+        [
+            (exposure, self.exposure_settle),
+            (gain, self.gain_settle),
+            (temperature, self.temperature_settle),
+        ]
+        .into_iter()
+        .filter_map(|(changed, settle)| changed.then_some(settle))
+        .max()
+        .unwrap_or(Duration::ZERO)
+    }
+
+    /// Check that a region of interest satisfies the alignment constraints.
+    ///
+    /// # Errors
+    /// [`Error::InvalidValue`] if the origin or size is not aligned.
+    pub fn check_roi(&self, roi: &ROI) -> Result<(), Error> {
+        let aligned = self.align_roi(roi);
+        if aligned != *roi {
+            return Err(Error::InvalidValue(format!(
+                "ROI {} not aligned, nearest valid ROI: {}",
+                roi, aligned
+            )));
+        }
+        Ok(())
+    }
+
+    /// Align a region of interest to the constraints, rounding the origin and the
+    /// size down to the nearest multiples. A non-zero size is kept at least one
+    /// multiple, and a size of zero (the full detector) is kept.
+    pub fn align_roi(&self, roi: &ROI) -> ROI {
+        let down = |val: u32, multiple: u32| val - val % multiple.max(1);
+        let size = |val: u32, multiple: u32| match val {
+            0 => 0,
+            _ => down(val, multiple).max(multiple.max(1)),
+        };
+        let (mx, my) = self.roi_origin_multiple;
+        let (mw, mh) = self.roi_size_multiple;
+        ROI {
+            x_min: down(roi.x_min, mx),
+            y_min: down(roi.y_min, my),
+            width: size(roi.width, mw),
+            height: size(roi.height, mh),
+            ..*roi
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roi(x_min: u32, y_min: u32, width: u32, height: u32) -> ROI {
+        ROI {
+            x_min,
+            y_min,
+            width,
+            height,
+            bin_x: 2,
+            bin_y: 2,
+            skip_x: 1,
+            skip_y: 1,
+        }
+    }
+
+    fn quirks(origin: (u32, u32), size: (u32, u32)) -> Quirks {
+        Quirks {
+            roi_origin_multiple: origin,
+            roi_size_multiple: size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn align_roi() {
+        let quirks = quirks((4, 2), (8, 2));
+        assert_eq!(quirks.align_roi(&roi(5, 3, 17, 5)), roi(4, 2, 16, 4));
+        // Zero size is the full detector, smaller sizes are kept at one multiple.
+        assert_eq!(quirks.align_roi(&roi(0, 0, 0, 1)), roi(0, 0, 0, 2));
+        assert_eq!(quirks.align_roi(&roi(3, 1, 7, 0)), roi(0, 0, 8, 0));
+        let aligned = roi(8, 4, 16, 2);
+        assert_eq!(quirks.align_roi(&aligned), aligned);
+    }
+
+    #[test]
+    fn align_roi_zero_multiples() {
+        let quirks = quirks((0, 0), (0, 0));
+        let unaligned = roi(5, 3, 17, 5);
+        assert_eq!(quirks.align_roi(&unaligned), unaligned);
+        assert_eq!(quirks.check_roi(&unaligned), Ok(()));
+        assert_eq!(quirks.align_roi(&roi(0, 0, 0, 0)), roi(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn check_roi() {
+        let quirks = quirks((4, 4), (4, 4));
+        assert_eq!(quirks.check_roi(&roi(4, 8, 16, 0)), Ok(()));
+        assert!(matches!(
+            quirks.check_roi(&roi(4, 8, 15, 16)),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            quirks.check_roi(&roi(1, 8, 16, 16)),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(Quirks::default().check_roi(&roi(1, 3, 5, 7)).is_ok());
+    }
+
+    #[test]
+    fn settle_time() {
+        let quirks = Quirks {
+            gain_settle: Duration::from_millis(200),
+            exposure_settle: Duration::from_millis(100),
+            temperature_settle: Duration::from_secs(30),
+            ..Default::default()
+        };
+        assert_eq!(quirks.settle_time(false, false, false), Duration::ZERO);
+        assert_eq!(
+            quirks.settle_time(true, false, false),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            quirks.settle_time(true, true, false),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            quirks.settle_time(true, true, true),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            Quirks::default().settle_time(true, true, true),
+            Duration::ZERO
+        );
+    }
+}
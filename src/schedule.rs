@@ -0,0 +1,252 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::stats::mean_level;
+use crate::{CameraUnit, Clock, DynamicSerialImage, Error};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Exposure and gain settings.
+pub struct ExposureProfile {
+    /// The exposure time.
+    pub exposure: Duration,
+    /// The gain (in raw units).
+    pub gain: i64,
+}
+
+impl ExposureProfile {
+    /// Apply the profile to a camera, and wait for the settle time declared in the
+    /// quirks of the camera (see [`crate::Quirks`]) on the given clock.
+    pub fn apply(&self, cam: &mut dyn CameraUnit, clock: &dyn Clock) -> Result<(), Error> {
+        let settle = cam.get_quirks().settle_time(
+            self.exposure != cam.get_exposure(),
+            self.gain != cam.get_gain_raw(),
+            false,
+        );
+        cam.set_exposure(self.exposure)?;
+        cam.set_gain_raw(self.gain)?;
+        if !settle.is_zero() {
+            clock.sleep(settle);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Day or night mode of a [`DayNightScheduler`].
+pub enum DayNight {
+    /// Daytime mode.
+    Day,
+    /// Nighttime mode.
+    Night,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Switches a camera between day and night exposure profiles based on the measured
+/// frame brightness, as commonly required for all-sky cameras.
+///
+/// Each mode has its own switching threshold, measured with that mode's profile, which
+/// provides hysteresis. A switch happens only after the threshold is crossed for
+/// several consecutive frames. If a sun altitude is provided, it overrides the
+/// brightness when the sun is clearly above or below the horizon.
+pub struct DayNightScheduler {
+    /// The daytime profile.
+    pub day: ExposureProfile,
+    /// The nighttime profile.
+    pub night: ExposureProfile,
+    /// Switch from night to day when the mean frame level, as a fraction of full
+    /// scale, exceeds this value in night mode.
+    pub day_threshold: f64,
+    /// Switch from day to night when the mean frame level, as a fraction of full
+    /// scale, drops below this value in day mode.
+    pub night_threshold: f64,
+    /// The full-scale pixel level. Defaults to `u16::MAX`; set this to the saturation
+    /// level of cameras that do not scale their data to 16 bits, e.g.
+    /// [`crate::PixelBpp::full_scale`] of the camera bit depth.
+    #[serde(default = "default_full_scale")]
+    pub full_scale: f64,
+    /// The number of consecutive frames crossing a threshold required to switch.
+    pub frames: usize,
+    /// Sun altitude (in degrees) above which day mode is forced.
+    pub sun_day_altitude: f64,
+    /// Sun altitude (in degrees) below which night mode is forced.
+    pub sun_night_altitude: f64,
+    mode: DayNight,
+    count: usize,
+}
+
+fn default_full_scale() -> f64 {
+    u16::MAX as f64
+}
+
+impl DayNightScheduler {
+    /// Create a new scheduler starting in the given mode.
+    pub fn new(day: ExposureProfile, night: ExposureProfile, mode: DayNight) -> Self {
+        Self {
+            day,
+            night,
+            day_threshold: 0.8,
+            night_threshold: 0.05,
+            full_scale: default_full_scale(),
+            frames: 3,
+            sun_day_altitude: 0.0,
+            sun_night_altitude: -12.0,
+            mode,
+            count: 0,
+        }
+    }
+
+    /// Get the current mode.
+    pub fn mode(&self) -> DayNight {
+        self.mode
+    }
+
+    /// Get the profile of the current mode.
+    pub fn profile(&self) -> &ExposureProfile {
+        match self.mode {
+            DayNight::Day => &self.day,
+            DayNight::Night => &self.night,
+        }
+    }
+
+    /// Update the mode from a frame captured with the current profile.
+    ///
+    /// # Arguments
+    /// - `img` - The frame.
+    /// - `sun_altitude` - The sun altitude (in degrees), if known.
+    ///
+    /// # Returns
+    /// The new mode if the mode was switched.
+    pub fn update(
+        &mut self,
+        img: &DynamicSerialImage,
+        sun_altitude: Option<f64>,
+    ) -> Option<DayNight> {
+        let full_scale = self.full_scale.max(1.0);
+        self.update_level(|| mean_level(img) / full_scale, sun_altitude)
+    }
+
+    fn update_level(
+        &mut self,
+        level: impl FnOnce() -> f64,
+        sun_altitude: Option<f64>,
+    ) -> Option<DayNight> {
+        let target = match sun_altitude {
+            Some(alt) if alt >= self.sun_day_altitude => Some(DayNight::Day),
+            Some(alt) if alt <= self.sun_night_altitude => Some(DayNight::Night),
+            _ => None,
+        };
+        let switch = match target {
+            Some(mode) => {
+                // Frames counted before the hint must not switch once it goes away.
+                self.count = 0;
+                mode != self.mode
+            }
+            None => {
+                let level = level();
+                let crossed = match self.mode {
+                    DayNight::Day => level < self.night_threshold,
+                    DayNight::Night => level > self.day_threshold,
+                };
+                self.count = if crossed { self.count + 1 } else { 0 };
+                self.count >= self.frames.max(1)
+            }
+        };
+        if !switch {
+            return None;
+        }
+        self.count = 0;
+        self.mode = match self.mode {
+            DayNight::Day => DayNight::Night,
+            DayNight::Night => DayNight::Day,
+        };
+        log::info!("Switching to {:?} profile", self.mode);
+        Some(self.mode)
+    }
+
+    /// Capture a frame, update the mode, and apply the new profile to the camera
+    /// if the mode was switched.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `sun_altitude` - The sun altitude (in degrees), if known.
+    /// - `clock` - The clock to wait on for the settle time of the new profile.
+    pub fn capture_image(
+        &mut self,
+        cam: &mut dyn CameraUnit,
+        sun_altitude: Option<f64>,
+        clock: &dyn Clock,
+    ) -> Result<DynamicSerialImage, Error> {
+        let img = cam.capture_image()?;
+        if self.update(&img, sun_altitude).is_some() {
+            self.profile().apply(cam, clock)?;
+        }
+        Ok(img)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(mode: DayNight) -> DayNightScheduler {
+        let profile = |exposure| ExposureProfile {
+            exposure: Duration::from_millis(exposure),
+            gain: 0,
+        };
+        DayNightScheduler::new(profile(1), profile(10_000), mode)
+    }
+
+    #[test]
+    fn switches_after_consecutive_frames() {
+        let mut scheduler = scheduler(DayNight::Day);
+        // Between the thresholds: no switch in either mode.
+        for _ in 0..10 {
+            assert_eq!(scheduler.update_level(|| 0.5, None), None);
+        }
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        // An interruption restarts the count.
+        assert_eq!(scheduler.update_level(|| 0.06, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), Some(DayNight::Night));
+        assert_eq!(scheduler.profile().exposure, Duration::from_secs(10));
+        // The night threshold does not apply in night mode.
+        for _ in 0..10 {
+            assert_eq!(scheduler.update_level(|| 0.5, None), None);
+        }
+        for _ in 0..2 {
+            assert_eq!(scheduler.update_level(|| 0.9, None), None);
+        }
+        assert_eq!(scheduler.update_level(|| 0.9, None), Some(DayNight::Day));
+    }
+
+    #[test]
+    fn sun_altitude_overrides_brightness() {
+        let mut scheduler = scheduler(DayNight::Night);
+        assert_eq!(
+            scheduler.update_level(|| 0.0, Some(10.0)),
+            Some(DayNight::Day)
+        );
+        assert_eq!(scheduler.update_level(|| 0.0, Some(10.0)), None);
+        assert_eq!(
+            scheduler.update_level(|| 1.0, Some(-20.0)),
+            Some(DayNight::Night)
+        );
+        // Twilight: the brightness decides.
+        assert_eq!(scheduler.update_level(|| 0.9, Some(-6.0)), None);
+        assert_eq!(scheduler.mode(), DayNight::Night);
+    }
+
+    #[test]
+    fn sun_altitude_resets_count() {
+        let mut scheduler = scheduler(DayNight::Day);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, Some(5.0)), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), None);
+        assert_eq!(scheduler.update_level(|| 0.01, None), Some(DayNight::Night));
+    }
+}
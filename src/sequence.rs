@@ -0,0 +1,306 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CameraUnit, Clock, DynamicSerialImage, Error, ObservationInfo, ROI};
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A set of identical exposures in a [`SequenceTarget`].
+pub struct SequenceStep {
+    /// The exposure time.
+    pub exposure: Duration,
+    /// The gain (in raw units). The current gain is kept if `None`.
+    #[serde(default)]
+    pub gain: Option<i64>,
+    /// The region of interest. The current region of interest is kept if `None`.
+    #[serde(default)]
+    pub roi: Option<ROI>,
+    /// The number of frames to capture.
+    #[serde(default = "one")]
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A target of an acquisition [`Sequence`].
+pub struct SequenceTarget {
+    /// Information about the observation, written into the metadata of every frame.
+    #[serde(default)]
+    pub info: ObservationInfo,
+    /// The steps, executed in order.
+    pub steps: Vec<SequenceStep>,
+    /// The number of times the steps are repeated.
+    #[serde(default = "one")]
+    pub repeat: u32,
+    /// Hooks run before the target (see [`SequenceHandler::hook`]).
+    #[serde(default)]
+    pub before: Vec<String>,
+    /// Hooks run after the target (see [`SequenceHandler::hook`]).
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// An acquisition plan, that can be written and shared as a file.
+///
+/// The structure is serde-based, so it can be stored in any format supported by
+/// serde. [`Sequence::load`] and [`Sequence::save`] use TOML for files with a `.toml`
+/// extension (requires the `toml` feature), and JSON otherwise.
+pub struct Sequence {
+    /// The name of the sequence.
+    pub name: String,
+    /// The targets, executed in order.
+    pub targets: Vec<SequenceTarget>,
+}
+
+/// Receives the frames and hooks of a running [`Sequence`].
+pub trait SequenceHandler {
+    /// Handle a captured frame.
+    ///
+    /// # Arguments
+    /// - `target` - The index of the target in the sequence.
+    /// - `step` - The index of the step in the target.
+    /// - `img` - The frame, with the observation information of the target in the metadata.
+    fn frame(&mut self, target: usize, step: usize, img: DynamicSerialImage) -> Result<(), Error>;
+
+    /// Run a hook named in [`SequenceTarget::before`] or [`SequenceTarget::after`].
+    ///
+    /// Defaults to logging and ignoring the hook.
+    fn hook(&mut self, name: &str) -> Result<(), Error> {
+        log::warn!("Ignoring sequence hook {}", name);
+        Ok(())
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+#[cfg(feature = "toml")]
+fn from_toml(data: &str) -> Result<Sequence, String> {
+    toml::from_str(data).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "toml"))]
+fn from_toml(_data: &str) -> Result<Sequence, String> {
+    Err("TOML sequences require the `toml` feature".to_string())
+}
+
+#[cfg(feature = "toml")]
+fn to_toml(seq: &Sequence) -> Result<String, String> {
+    toml::to_string_pretty(seq).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "toml"))]
+fn to_toml(_seq: &Sequence) -> Result<String, String> {
+    Err("TOML sequences require the `toml` feature".to_string())
+}
+
+impl Sequence {
+    /// Load a sequence from a TOML or JSON file, and validate it (see [`Sequence::validate`]).
+    ///
+    /// # Errors
+    /// - [`Error::InvalidPath`] if the file can not be read.
+    /// - [`Error::InvalidFormat`] if the file can not be parsed.
+    /// - [`Error::InvalidValue`] if the sequence is invalid.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let data = fs::read_to_string(path)
+            .map_err(|e| Error::InvalidPath(format!("{}: {}", path.display(), e)))?;
+        let seq = if is_toml(path) {
+            from_toml(&data)
+        } else {
+            serde_json::from_str(&data).map_err(|e| e.to_string())
+        }
+        .map_err(|e| Error::InvalidFormat(format!("{}: {}", path.display(), e)))?;
+        seq.validate(None)?;
+        Ok(seq)
+    }
+
+    /// Save the sequence to a TOML or JSON file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let data = if is_toml(path) {
+            to_toml(self)
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+        }
+        .map_err(Error::InvalidFormat)?;
+        fs::write(path, data).map_err(|e| Error::InvalidPath(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Validate the sequence.
+    ///
+    /// Every target must have at least one step, and repeat and frame counts must be
+    /// positive. If a camera is given, the exposure times must be within the exposure
+    /// limits of the camera, and the regions of interest must fit on the detector and
+    /// satisfy its alignment constraints (see [`crate::Quirks`]).
+    ///
+    /// # Errors
+    /// [`Error::InvalidValue`] describing the first problem found.
+    pub fn validate(&self, cam: Option<&dyn CameraUnit>) -> Result<(), Error> {
+        let invalid = |target: usize, msg: String| {
+            Err(Error::InvalidValue(format!("Target {}: {}", target, msg)))
+        };
+        for (tidx, target) in self.targets.iter().enumerate() {
+            if target.steps.is_empty() {
+                return invalid(tidx, "No steps".to_string());
+            }
+            if target.repeat == 0 {
+                return invalid(tidx, "Repeat count is zero".to_string());
+            }
+            for (sidx, step) in target.steps.iter().enumerate() {
+                if step.count == 0 {
+                    return invalid(tidx, format!("Step {}: Frame count is zero", sidx));
+                }
+                let Some(cam) = cam else {
+                    continue;
+                };
+                if let (Ok(min), Ok(max)) = (cam.get_min_exposure(), cam.get_max_exposure()) {
+                    if step.exposure < min || step.exposure > max {
+                        return invalid(
+                            tidx,
+                            format!(
+                                "Step {}: Exposure {:?} outside [{:?}, {:?}]",
+                                sidx, step.exposure, min, max
+                            ),
+                        );
+                    }
+                }
+                if let Some(roi) = &step.roi {
+                    let fits = match roi.checked_sensor_rect() {
+                        Some(rect) => {
+                            rect.x + rect.width <= cam.get_ccd_width()
+                                && rect.y + rect.height <= cam.get_ccd_height()
+                        }
+                        None => false,
+                    };
+                    if !fits {
+                        return invalid(
+                            tidx,
+                            format!("Step {}: {} exceeds the detector", sidx, roi),
+                        );
+                    }
+                    cam.get_quirks()
+                        .check_roi(roi)
+                        .or_else(|e| invalid(tidx, format!("Step {}: {}", sidx, e)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate the sequence against a camera, and execute it.
+    ///
+    /// After the settings of a step are applied, the settle time of the changed
+    /// settings declared in the quirks of the camera is waited for before the first
+    /// exposure (see [`crate::Quirks`]). The exposure time, gain and region of interest
+    /// of the camera are restored when the sequence completes or fails.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `handler` - Receives the frames and hooks.
+    /// - `clock` - The clock to wait on, e.g. [`crate::SystemClock`].
+    pub fn run(
+        &self,
+        cam: &mut dyn CameraUnit,
+        handler: &mut dyn SequenceHandler,
+        clock: &dyn Clock,
+    ) -> Result<(), Error> {
+        self.validate(Some(&*cam))?;
+        let (exposure, gain, roi) = (cam.get_exposure(), cam.get_gain_raw(), *cam.get_roi());
+        let res = self.execute(cam, handler, clock);
+        cam.set_exposure(exposure)?;
+        if gain != cam.get_gain_raw() {
+            cam.set_gain_raw(gain)?;
+        }
+        cam.set_roi(&roi)?;
+        res
+    }
+
+    fn execute(
+        &self,
+        cam: &mut dyn CameraUnit,
+        handler: &mut dyn SequenceHandler,
+        clock: &dyn Clock,
+    ) -> Result<(), Error> {
+        let quirks = cam.get_quirks();
+        for (tidx, target) in self.targets.iter().enumerate() {
+            log::info!("{}: Starting target {}", self.name, tidx);
+            for hook in &target.before {
+                handler.hook(hook)?;
+            }
+            for _ in 0..target.repeat {
+                for (sidx, step) in target.steps.iter().enumerate() {
+                    let settle = quirks.settle_time(
+                        step.exposure != cam.get_exposure(),
+                        matches!(step.gain, Some(gain) if gain != cam.get_gain_raw()),
+                        false,
+                    );
+                    cam.set_exposure(step.exposure)?;
+                    if let Some(gain) = step.gain {
+                        cam.set_gain_raw(gain)?;
+                    }
+                    if let Some(roi) = &step.roi {
+                        cam.set_roi(roi)?;
+                    }
+                    if !settle.is_zero() {
+                        clock.sleep(settle);
+                    }
+                    for _ in 0..step.count {
+                        let mut img = cam.capture_image()?;
+                        target.info.stamp(&mut img);
+                        handler.frame(tidx, sidx, img)?;
+                    }
+                }
+            }
+            for hook in &target.after {
+                handler.hook(hook)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence() -> Sequence {
+        Sequence {
+            name: "test".to_string(),
+            targets: vec![SequenceTarget {
+                info: ObservationInfo::default(),
+                steps: vec![SequenceStep {
+                    exposure: Duration::from_millis(1500),
+                    gain: Some(100),
+                    roi: Some("0,0,100,100,2,2".parse().unwrap()),
+                    count: 3,
+                }],
+                repeat: 2,
+                before: vec!["open_dome".to_string()],
+                after: Vec::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn validate_sequence() {
+        let mut seq = sequence();
+        assert_eq!(seq.validate(None), Ok(()));
+        seq.targets[0].steps[0].count = 0;
+        assert!(matches!(seq.validate(None), Err(Error::InvalidValue(_))));
+        seq.targets[0].steps.clear();
+        assert!(matches!(seq.validate(None), Err(Error::InvalidValue(_))));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip() {
+        let seq = sequence();
+        assert_eq!(from_toml(&to_toml(&seq).unwrap()), Ok(seq));
+    }
+}
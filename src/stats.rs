@@ -0,0 +1,148 @@
+use crate::DynamicSerialImage;
+
+/// Get the luminance pixel values of an image.
+pub(crate) fn luma_pixels(img: &DynamicSerialImage) -> Vec<u16> {
+    img.into_luma().into_vec()
+}
+
+/// Get the mean of a set of pixel values.
+pub(crate) fn mean(pixels: &[u16]) -> f64 {
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    pixels.iter().map(|&p| p as f64).sum::<f64>() / pixels.len() as f64
+}
+
+/// Get the mean pixel level of an image.
+pub(crate) fn mean_level(img: &DynamicSerialImage) -> f64 {
+    mean(&luma_pixels(img))
+}
+
+/// Get the value at a given percentile (in `[0, 1]`) of a set of pixel values.
+pub(crate) fn percentile(pixels: &[u16], pct: f64) -> f64 {
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = pixels.to_vec();
+    let idx = ((sorted.len() - 1) as f64 * pct.clamp(0.0, 1.0)).round() as usize;
+    let (_, value, _) = sorted.select_nth_unstable(idx);
+    *value as f64
+}
+
+/// Detection threshold for objects, as a multiple of the background noise.
+const DETECTION_SIGMA: f64 = 3.0;
+
+/// Get the background level and the detection threshold of a set of pixel values.
+///
+/// The background is estimated as the median pixel value, and pixels brighter than
+/// the background by more than [`DETECTION_SIGMA`] times the robust standard
+/// deviation are considered part of an object.
+fn detection_threshold(pixels: &[u16]) -> (f64, f64) {
+    let background = percentile(pixels, 0.5);
+    (
+        background,
+        background + DETECTION_SIGMA * robust_sigma(pixels),
+    )
+}
+
+/// Get the background-subtracted, intensity-weighted centroid `(x, y)` of the
+/// pixels above the detection threshold in an image of the given width.
+///
+/// Returns `None` if no pixel lies above the detection threshold.
+pub(crate) fn centroid(pixels: &[u16], width: usize) -> Option<(f64, f64)> {
+    if width == 0 {
+        return None;
+    }
+    let (background, threshold) = detection_threshold(pixels);
+    let (mut sum, mut sum_x, mut sum_y) = (0.0, 0.0, 0.0);
+    for (idx, &p) in pixels.iter().enumerate() {
+        if p as f64 > threshold {
+            let weight = p as f64 - background;
+            sum += weight;
+            sum_x += weight * (idx % width) as f64;
+            sum_y += weight * (idx / width) as f64;
+        }
+    }
+    if sum > 0.0 {
+        Some((sum_x / sum, sum_y / sum))
+    } else {
+        None
+    }
+}
+
+/// Get the half flux radius (in pixels) of the brightest object in an image of the
+/// given width, measured around the background-subtracted centroid.
+///
+/// Only pixels above the detection threshold contribute, so that the background
+/// noise does not inflate the radius. Returns `None` if no pixel lies above the
+/// detection threshold.
+pub(crate) fn half_flux_radius(pixels: &[u16], width: usize) -> Option<f64> {
+    let (cx, cy) = centroid(pixels, width)?;
+    let (background, threshold) = detection_threshold(pixels);
+    let (mut sum, mut sum_r) = (0.0, 0.0);
+    for (idx, &p) in pixels.iter().enumerate() {
+        if p as f64 > threshold {
+            let weight = p as f64 - background;
+            let dx = (idx % width) as f64 - cx;
+            let dy = (idx / width) as f64 - cy;
+            sum += weight;
+            sum_r += weight * (dx * dx + dy * dy).sqrt();
+        }
+    }
+    Some(sum_r / sum)
+}
+
+/// Get a robust estimate of the standard deviation of a set of pixel values,
+/// from the median absolute deviation.
+pub(crate) fn robust_sigma(pixels: &[u16]) -> f64 {
+    let median = percentile(pixels, 0.5);
+    let deviations: Vec<u16> = pixels
+        .iter()
+        .map(|&p| (p as f64 - median).abs().round() as u16)
+        .collect();
+    1.4826 * percentile(&deviations, 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 16x16 frame with a noisy background and a symmetric object centered at (10, 5).
+    fn frame() -> Vec<u16> {
+        let width = 16;
+        let mut pixels: Vec<u16> = (0..width * width).map(|i| 100 + (i % 3) as u16).collect();
+        for (dx, dy, p) in [
+            (0i32, 0i32, 1000u16),
+            (1, 0, 400),
+            (-1, 0, 400),
+            (0, 1, 400),
+            (0, -1, 400),
+        ] {
+            pixels[(5 + dy) as usize * width + (10 + dx) as usize] += p;
+        }
+        pixels
+    }
+
+    #[test]
+    fn centroid_ignores_background_noise() {
+        let (x, y) = centroid(&frame(), 16).unwrap();
+        assert!((x - 10.0).abs() < 0.01, "{}", x);
+        assert!((y - 5.0).abs() < 0.01, "{}", y);
+        assert_eq!(centroid(&[100; 16], 4), None);
+        assert_eq!(centroid(&frame(), 0), None);
+    }
+
+    #[test]
+    fn half_flux_radius_ignores_background_noise() {
+        let hfr = half_flux_radius(&frame(), 16).unwrap();
+        // Flux-weighted mean radius of the object: 1600 ADU at 1 pixel out of 2600 ADU.
+        assert!((hfr - 1600.0 / 2600.0).abs() < 0.01, "{}", hfr);
+        assert_eq!(half_flux_radius(&[100; 16], 4), None);
+    }
+
+    #[test]
+    fn robust_sigma_of_constant_is_zero() {
+        assert_eq!(robust_sigma(&[7; 9]), 0.0);
+        assert_eq!(percentile(&[5, 1, 3], 0.5), 3.0);
+    }
+}
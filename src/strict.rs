@@ -0,0 +1,337 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::forward::forward_camera_unit;
+use crate::{AnyCameraUnit, CameraUnit, DynamicSerialImage, Error, FrameCallback, RoiImage, ROI};
+
+/// Wrapper around a camera that panics on violations of the [`CameraUnit`]
+/// contract, to surface driver bugs early in development builds.
+///
+/// The following invariants are checked:
+/// - The region of interest returned by [`CameraUnit::set_roi`] equals the one
+///   requested (unless the full detector was requested).
+/// - [`CameraUnit::image_ready`] is `true` before [`CameraUnit::download_image`] is called.
+/// - The exposure set by [`CameraUnit::set_exposure`] is within the exposure limits,
+///   and is reported by [`CameraUnit::get_exposure`].
+/// - Captured images, including frames delivered to the callback registered with
+///   [`CameraUnit::set_frame_callback`], are consistent with the region of interest
+///   (see [`ROI::check_image`]).
+///
+/// The checks are compiled out in release builds, where all calls are passed
+/// through to the wrapped camera.
+pub struct StrictCamera {
+    cam: AnyCameraUnit,
+    roi: Arc<Mutex<ROI>>,
+}
+
+impl StrictCamera {
+    /// Wrap a camera.
+    pub fn new(cam: AnyCameraUnit) -> Self {
+        let roi = Arc::new(Mutex::new(*cam.get_roi()));
+        Self { cam, roi }
+    }
+
+    /// Unwrap the camera.
+    pub fn into_inner(self) -> AnyCameraUnit {
+        self.cam
+    }
+
+    fn check_image(&self, img: &DynamicSerialImage) {
+        if cfg!(debug_assertions) {
+            if let Err(e) = self.cam.get_roi().check_image(img) {
+                panic!("{}: {}", self.cam.camera_name(), e);
+            }
+        }
+    }
+}
+
+impl CameraUnit for StrictCamera {
+    forward_camera_unit!(cam);
+
+    fn capture_image(&self) -> Result<DynamicSerialImage, Error> {
+        let img = self.cam.capture_image()?;
+        self.check_image(&img);
+        Ok(img)
+    }
+
+    fn download_image(&self) -> Result<DynamicSerialImage, Error> {
+        debug_assert!(
+            self.cam.image_ready()?,
+            "{}: download_image called before image_ready",
+            self.cam.camera_name()
+        );
+        let img = self.cam.download_image()?;
+        self.check_image(&img);
+        Ok(img)
+    }
+
+    fn download_tile(&self, tile: &ROI) -> Result<DynamicSerialImage, Error> {
+        self.cam.download_tile(tile)
+    }
+
+    fn get_partial_readout(&self) -> Result<DynamicSerialImage, Error> {
+        self.cam.get_partial_readout()
+    }
+
+    fn next_frame(&self, timeout: Duration) -> Result<DynamicSerialImage, Error> {
+        debug_assert!(
+            self.cam.is_streaming(),
+            "{}: next_frame called without a stream",
+            self.cam.camera_name()
+        );
+        let img = self.cam.next_frame(timeout)?;
+        self.check_image(&img);
+        Ok(img)
+    }
+
+    fn set_frame_callback(&mut self, mut callback: FrameCallback) -> Result<(), Error> {
+        if !cfg!(debug_assertions) {
+            return self.cam.set_frame_callback(callback);
+        }
+        let roi = self.roi.clone();
+        let name = self.cam.camera_name().to_string();
+        self.cam.set_frame_callback(Box::new(move |img| {
+            let roi = *roi.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(e) = roi.check_image(&img) {
+                panic!("{}: {}", name, e);
+            }
+            callback(img)
+        }))
+    }
+
+    fn set_exposure(&mut self, exposure: Duration) -> Result<Duration, Error> {
+        let exposure = self.cam.set_exposure(exposure)?;
+        if let (Ok(min), Ok(max)) = (self.cam.get_min_exposure(), self.cam.get_max_exposure()) {
+            debug_assert!(
+                min <= exposure && exposure <= max,
+                "{}: Exposure {:?} outside [{:?}, {:?}]",
+                self.cam.camera_name(),
+                exposure,
+                min,
+                max
+            );
+        }
+        debug_assert_eq!(
+            exposure,
+            self.cam.get_exposure(),
+            "{}: get_exposure does not match set_exposure",
+            self.cam.camera_name()
+        );
+        Ok(exposure)
+    }
+
+    fn set_roi(&mut self, roi: &ROI) -> Result<&ROI, Error> {
+        let full = roi.x_min == 0 && roi.y_min == 0 && roi.width == 0 && roi.height == 0;
+        let name = self.cam.camera_name().to_string();
+        let set = self.cam.set_roi(roi)?;
+        debug_assert!(
+            full || set == roi,
+            "{}: set_roi returned {}, expected {}",
+            name,
+            set,
+            roi
+        );
+        *self.roi.lock().unwrap_or_else(|e| e.into_inner()) = *set;
+        Ok(set)
+    }
+
+    fn capture_multi_roi(&self) -> Result<Vec<RoiImage>, Error> {
+        let imgs = self.cam.capture_multi_roi()?;
+        if cfg!(debug_assertions) {
+            for img in &imgs {
+                if let Err(e) = img.roi.check_image(&img.image) {
+                    panic!("{}: Window {}: {}", self.cam.camera_name(), img.index, e);
+                }
+            }
+        }
+        Ok(imgs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PixelBpp, SerialImageBuffer};
+
+    type SharedCallback = Arc<Mutex<Option<FrameCallback>>>;
+
+    struct MockCamera {
+        roi: ROI,
+        shift_roi: bool,
+        ready: bool,
+        exposure: Duration,
+        callback: SharedCallback,
+    }
+
+    impl CameraUnit for MockCamera {
+        fn get_vendor(&self) -> &str {
+            "mock"
+        }
+
+        fn capture_image(&self) -> Result<DynamicSerialImage, Error> {
+            Ok(image(self.roi.width, self.roi.height))
+        }
+
+        fn start_exposure(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn download_image(&self) -> Result<DynamicSerialImage, Error> {
+            self.capture_image()
+        }
+
+        fn image_ready(&self) -> Result<bool, Error> {
+            Ok(self.ready)
+        }
+
+        fn exposure_remaining(&self) -> Result<Duration, Error> {
+            Ok(Duration::ZERO)
+        }
+
+        fn set_frame_callback(&mut self, callback: FrameCallback) -> Result<(), Error> {
+            *self.callback.lock().unwrap() = Some(callback);
+            Ok(())
+        }
+
+        fn set_exposure(&mut self, exposure: Duration) -> Result<Duration, Error> {
+            self.exposure = exposure;
+            Ok(exposure)
+        }
+
+        fn get_exposure(&self) -> Duration {
+            self.exposure
+        }
+
+        fn get_min_exposure(&self) -> Result<Duration, Error> {
+            Ok(Duration::from_millis(1))
+        }
+
+        fn get_max_exposure(&self) -> Result<Duration, Error> {
+            Ok(Duration::from_secs(1))
+        }
+
+        fn set_roi(&mut self, roi: &ROI) -> Result<&ROI, Error> {
+            self.roi = *roi;
+            if self.shift_roi {
+                self.roi.x_min += 1;
+            }
+            Ok(&self.roi)
+        }
+
+        fn set_bpp(&mut self, bpp: PixelBpp) -> Result<PixelBpp, Error> {
+            Ok(bpp)
+        }
+
+        fn get_bpp(&self) -> PixelBpp {
+            PixelBpp::Bpp16
+        }
+
+        fn get_roi(&self) -> &ROI {
+            &self.roi
+        }
+
+        fn camera_ready(&self) -> bool {
+            true
+        }
+
+        fn camera_name(&self) -> &str {
+            "mock"
+        }
+
+        fn cancel_capture(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn is_capturing(&self) -> bool {
+            false
+        }
+
+        fn get_ccd_width(&self) -> u32 {
+            64
+        }
+
+        fn get_ccd_height(&self) -> u32 {
+            64
+        }
+    }
+
+    fn image(width: u32, height: u32) -> DynamicSerialImage {
+        let (width, height) = (width as usize, height as usize);
+        DynamicSerialImage::from(
+            SerialImageBuffer::from_vec(width, height, vec![0u16; width * height]).unwrap(),
+        )
+    }
+
+    fn roi(width: u32, height: u32) -> ROI {
+        ROI {
+            x_min: 0,
+            y_min: 0,
+            width,
+            height,
+            bin_x: 1,
+            bin_y: 1,
+            skip_x: 1,
+            skip_y: 1,
+        }
+    }
+
+    fn strict(shift_roi: bool, ready: bool) -> (StrictCamera, SharedCallback) {
+        let callback = SharedCallback::default();
+        let cam = MockCamera {
+            roi: roi(8, 8),
+            shift_roi,
+            ready,
+            exposure: Duration::from_millis(10),
+            callback: callback.clone(),
+        };
+        (StrictCamera::new(Box::new(cam)), callback)
+    }
+
+    fn deliver(callback: &SharedCallback, img: DynamicSerialImage) {
+        (callback.lock().unwrap().as_mut().unwrap())(img)
+    }
+
+    #[test]
+    fn strict_camera_passes_valid_calls() {
+        let (mut cam, callback) = strict(false, true);
+        cam.set_roi(&roi(4, 2)).unwrap();
+        cam.set_exposure(Duration::from_millis(100)).unwrap();
+        cam.capture_image().unwrap();
+        cam.download_image().unwrap();
+        let frames = Arc::new(Mutex::new(0));
+        let count = frames.clone();
+        cam.set_frame_callback(Box::new(move |_| *count.lock().unwrap() += 1))
+            .unwrap();
+        deliver(&callback, image(4, 2));
+        assert_eq!(*frames.lock().unwrap(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "set_roi returned")]
+    fn strict_camera_roi_mismatch() {
+        let (mut cam, _) = strict(true, true);
+        let _ = cam.set_roi(&roi(4, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "download_image called before image_ready")]
+    fn strict_camera_download_before_ready() {
+        let (cam, _) = strict(false, false);
+        let _ = cam.download_image();
+    }
+
+    #[test]
+    #[should_panic(expected = "outside")]
+    fn strict_camera_exposure_out_of_range() {
+        let (mut cam, _) = strict(false, true);
+        let _ = cam.set_exposure(Duration::from_secs(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "Width is 4, expected 8")]
+    fn strict_camera_callback_frame_mismatch() {
+        let (mut cam, callback) = strict(false, true);
+        cam.set_frame_callback(Box::new(|_| {})).unwrap();
+        deliver(&callback, image(4, 8));
+    }
+}
@@ -0,0 +1,167 @@
+use std::path::Path;
+
+use fitsio::tables::{ColumnDataType, ColumnDescription};
+use fitsio::FitsFile;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Data stored in a table column.
+pub enum ColumnData {
+    /// 16-bit signed integers.
+    I16(Vec<i16>),
+    /// 32-bit signed integers.
+    I32(Vec<i32>),
+    /// 64-bit signed integers.
+    I64(Vec<i64>),
+    /// 32-bit floating point numbers.
+    F32(Vec<f32>),
+    /// 64-bit floating point numbers.
+    F64(Vec<f64>),
+    /// Strings.
+    String(Vec<String>),
+}
+
+impl ColumnData {
+    /// Get the number of rows in the column.
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::I16(v) => v.len(),
+            ColumnData::I32(v) => v.len(),
+            ColumnData::I64(v) => v.len(),
+            ColumnData::F32(v) => v.len(),
+            ColumnData::F64(v) => v.len(),
+            ColumnData::String(v) => v.len(),
+        }
+    }
+
+    /// Check if the column is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A named table column.
+pub struct TableColumn {
+    /// The column name.
+    pub name: String,
+    /// The column data.
+    pub data: ColumnData,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A table of equal-length columns, such as an event list from a
+/// photon-counting detector.
+pub struct TableData {
+    name: String,
+    columns: Vec<TableColumn>,
+}
+
+impl TableData {
+    /// Create a new empty table.
+    ///
+    /// # Arguments
+    /// - `name` - The table name, used as the `EXTNAME` of the FITS extension.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Get the table name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the table columns.
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+
+    /// Get the number of rows in the table.
+    pub fn rows(&self) -> usize {
+        self.columns.first().map_or(0, |c| c.data.len())
+    }
+
+    /// Add a column to the table.
+    ///
+    /// # Arguments
+    /// - `name` - The column name.
+    /// - `data` - The column data.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidValue`] if a column with the same name exists.
+    /// - [`Error::InvalidSize`] if the column length does not match the
+    ///   number of rows in the table.
+    pub fn add_column(&mut self, name: &str, data: ColumnData) -> Result<&mut Self, Error> {
+        if self.columns.iter().any(|c| c.name == name) {
+            return Err(Error::InvalidValue(format!("Duplicate column: {}", name)));
+        }
+        if !self.columns.is_empty() && data.len() != self.rows() {
+            return Err(Error::InvalidSize(data.len()));
+        }
+        self.columns.push(TableColumn {
+            name: name.to_string(),
+            data,
+        });
+        Ok(self)
+    }
+
+    /// Save the table as a binary table extension in a FITS file.
+    ///
+    /// If the file exists, the table is appended as a new extension, so event
+    /// lists can be stored alongside images. Otherwise a new file with an
+    /// empty primary HDU is created.
+    ///
+    /// # Arguments
+    /// - `path` - The path to the FITS file.
+    pub fn save_fits_table(&self, path: &Path) -> Result<(), Error> {
+        if self.columns.is_empty() {
+            return Err(Error::InvalidSize(0));
+        }
+        let mut fptr = if path.exists() {
+            FitsFile::edit(path)
+        } else {
+            FitsFile::create(path).open()
+        }
+        .map_err(|e| Error::FitsError(e.to_string()))?;
+        let descriptions = self
+            .columns
+            .iter()
+            .map(|c| {
+                let desc = ColumnDescription::new(c.name.as_str());
+                match &c.data {
+                    ColumnData::I16(_) => desc.with_type(ColumnDataType::Short),
+                    ColumnData::I32(_) => desc.with_type(ColumnDataType::Int),
+                    ColumnData::I64(_) => desc.with_type(ColumnDataType::Long),
+                    ColumnData::F32(_) => desc.with_type(ColumnDataType::Float),
+                    ColumnData::F64(_) => desc.with_type(ColumnDataType::Double),
+                    ColumnData::String(v) => desc
+                        .with_type(ColumnDataType::String)
+                        .that_repeats(v.iter().map(|s| s.len()).max().unwrap_or(0).max(1)),
+                }
+                .create()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::FitsError(e.to_string()))?;
+        let hdu = fptr
+            .create_table(self.name.as_str(), &descriptions)
+            .map_err(|e| Error::FitsError(e.to_string()))?;
+        for c in &self.columns {
+            let name = c.name.as_str();
+            match &c.data {
+                ColumnData::I16(v) => hdu.write_col(&mut fptr, name, v),
+                ColumnData::I32(v) => hdu.write_col(&mut fptr, name, v),
+                ColumnData::I64(v) => hdu.write_col(&mut fptr, name, v),
+                ColumnData::F32(v) => hdu.write_col(&mut fptr, name, v),
+                ColumnData::F64(v) => hdu.write_col(&mut fptr, name, v),
+                ColumnData::String(v) => hdu.write_col(&mut fptr, name, v),
+            }
+            .map_err(|e| Error::FitsError(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use crate::stats::{centroid, half_flux_radius, luma_pixels};
+use crate::{CameraUnit, DynamicSerialImage, Error, ROI};
+
+/// Moves the region of interest of a camera between frames to keep a target
+/// (e.g. a planet drifting without a tracking mount) centered.
+///
+/// The target position is the intensity-weighted centroid of the frame. The
+/// region of interest is moved only when the target drifts further than the
+/// dead band from the center, to avoid chasing noise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackingRoi {
+    deadband: f64,
+}
+
+impl TrackingRoi {
+    /// Create a new tracking controller.
+    ///
+    /// # Arguments
+    /// - `deadband` - The allowed drift (in pixels of the frame) of the target from the
+    ///   center of the region of interest before it is moved.
+    pub fn new(deadband: u32) -> Self {
+        Self {
+            deadband: deadband as f64,
+        }
+    }
+
+    /// Update the region of interest of the camera from the last captured frame.
+    ///
+    /// # Returns
+    /// The new region of interest if it was moved, `None` if the target is within the
+    /// dead band or could not be found in the frame, or error.
+    pub fn update(
+        &self,
+        cam: &mut dyn CameraUnit,
+        img: &DynamicSerialImage,
+    ) -> Result<Option<ROI>, Error> {
+        let Some((x, y)) = centroid(&luma_pixels(img), img.width()) else {
+            return Ok(None);
+        };
+        let roi = *cam.get_roi();
+        let dx = x - img.width() as f64 / 2.0;
+        let dy = y - img.height() as f64 / 2.0;
+        if dx.abs() <= self.deadband && dy.abs() <= self.deadband {
+            return Ok(None);
+        }
+        // With decimation, a frame pixel covers `skip` binned pixels.
+        let dx = dx * roi.skip_x.max(1) as f64;
+        let dy = dy * roi.skip_y.max(1) as f64;
+        let rect = roi.sensor_rect();
+        let max_x = cam.get_ccd_width().saturating_sub(rect.width) / roi.bin_x.max(1);
+        let max_y = cam.get_ccd_height().saturating_sub(rect.height) / roi.bin_y.max(1);
+        let new = ROI {
+            x_min: (roi.x_min as f64 + dx).round().clamp(0.0, max_x as f64) as u32,
+            y_min: (roi.y_min as f64 + dy).round().clamp(0.0, max_y as f64) as u32,
+            ..roi
+        };
+        if new == roi {
+            return Ok(None);
+        }
+        cam.set_roi(&new).map(|roi| Some(*roi))
+    }
+}
+
+/// A small, high-cadence region of interest around a point of interest, with a
+/// per-frame half flux radius (HFR) measurement, for "focus zoom box" features.
+///
+/// The original region of interest of the camera is restored by [`FocusZoom::stop`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FocusZoom {
+    roi: ROI,
+    orig: ROI,
+}
+
+impl FocusZoom {
+    /// Start a focus zoom.
+    ///
+    /// # Arguments
+    /// - `cam` - The camera.
+    /// - `x` - The X coordinate (in un-binned sensor pixels) of the point of interest.
+    /// - `y` - The Y coordinate (in un-binned sensor pixels) of the point of interest.
+    /// - `size` - The width and height (in pixels) of the zoom window.
+    pub fn start(cam: &mut dyn CameraUnit, x: u32, y: u32, size: u32) -> Result<Self, Error> {
+        let orig = *cam.get_roi();
+        let width = size.max(1).min(cam.get_ccd_width());
+        let height = size.max(1).min(cam.get_ccd_height());
+        let roi = ROI {
+            x_min: x.saturating_sub(width / 2).min(cam.get_ccd_width() - width),
+            y_min: y
+                .saturating_sub(height / 2)
+                .min(cam.get_ccd_height() - height),
+            width,
+            height,
+            bin_x: 1,
+            bin_y: 1,
+            skip_x: 1,
+            skip_y: 1,
+        };
+        let roi = *cam.set_roi(&roi)?;
+        Ok(Self { roi, orig })
+    }
+
+    /// Get the region of interest of the zoom window.
+    pub fn roi(&self) -> &ROI {
+        &self.roi
+    }
+
+    /// Capture the next frame of the zoom window.
+    ///
+    /// # Returns
+    /// The frame, and its half flux radius (in pixels) if a star was found, or error.
+    pub fn next_frame(
+        &self,
+        cam: &dyn CameraUnit,
+    ) -> Result<(DynamicSerialImage, Option<f64>), Error> {
+        let img = cam.capture_image()?;
+        let hfr = half_flux_radius(&luma_pixels(&img), img.width());
+        Ok((img, hfr))
+    }
+
+    /// Stop the focus zoom, and restore the original region of interest.
+    pub fn stop(self, cam: &mut dyn CameraUnit) -> Result<(), Error> {
+        cam.set_roi(&self.orig).map(|_| ())
+    }
+}